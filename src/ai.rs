@@ -0,0 +1,151 @@
+use super::*;
+use bevy::ecs::system::SystemParam;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// The computer player, searching for its moves on its turns
+#[derive(Resource, Clone)]
+pub struct AiPlayer {
+    pub color: PieceColor,
+    pub difficulty: Difficulty,
+    pub personality: Personality,
+    pub limits: search::Limits,
+    /// Varies the randomness of the weaker levels from game to game
+    pub seed: u64,
+}
+
+impl AiPlayer {
+    pub fn new(color: PieceColor, difficulty: Difficulty, personality: Personality) -> Self {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+
+        Self {
+            color,
+            difficulty,
+            personality,
+            limits: difficulty.limits(),
+            seed,
+        }
+    }
+
+    /// Writes who the computer plays and how into the tags saved with the game
+    pub fn record(&self, game: &mut Game) {
+        let name = match self.color {
+            PieceColor::White => "White",
+            PieceColor::Black => "Black",
+        };
+
+        game.set_tag(name, "Computer");
+        game.set_tag("Difficulty", &format!("{:?}", self.difficulty));
+        game.set_tag("Personality", &format!("{:?}", self.personality));
+    }
+}
+
+/// The search running in the background for the position it was started from
+#[derive(Component)]
+pub struct Thinking {
+    pub position: rules::Position,
+    pub task: Task<Option<rules::Move>>,
+    /// Raised to cut the search short once its move is no longer wanted
+    pub stop: Arc<AtomicBool>,
+}
+
+// NOTE: Despawning the entity stops the search instead of leaving it running in the pool
+impl Drop for Thinking {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+pub struct AiPlugin;
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                start_thinking
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(resource_exists::<AiPlayer>()),
+                play_best_move.run_if(viewing_latest),
+            ),
+        );
+    }
+}
+
+/// Run condition keeping the human from moving the pieces of the `AiPlayer` or `EnginePlayer`
+pub fn human_turn(game: Res<Game>, computers: Computers) -> bool {
+    !computers.play(game.position.side)
+}
+
+/// The players that are not the human at the board
+#[derive(SystemParam)]
+pub struct Computers<'w> {
+    ai: Option<Res<'w, AiPlayer>>,
+    engine: Option<Res<'w, EnginePlayer>>,
+}
+
+impl Computers<'_> {
+    pub fn play(&self, color: PieceColor) -> bool {
+        self.ai.as_ref().is_some_and(|ai| ai.color == color)
+            || self
+                .engine
+                .as_ref()
+                .is_some_and(|engine| engine.color == color && engine.running())
+    }
+}
+
+fn start_thinking(
+    mut commands: Commands,
+    ai: Res<AiPlayer>,
+    game: Res<Game>,
+    thinking: Query<&Thinking>,
+) {
+    if ai.color != game.position.side || !thinking.is_empty() {
+        return;
+    }
+
+    let position = game.position.clone();
+    let limits = ai.limits.clone();
+    let skill = ai
+        .difficulty
+        .skill(ai.personality, ai.seed ^ game.moves.len() as u64);
+    let stop = Arc::new(AtomicBool::new(false));
+    let task = AsyncComputeTaskPool::get().spawn({
+        let position = position.clone();
+        let stop = stop.clone();
+        async move { search::search_with(&position, &limits, skill, &stop, |_| {}).best }
+    });
+
+    commands.spawn(Thinking {
+        position,
+        task,
+        stop,
+    });
+}
+
+/// Sends the move found through the same `MoveEvent` as the human moves,
+/// dropping searches of positions that were taken back meanwhile
+fn play_best_move(
+    mut commands: Commands,
+    mut events: EventWriter<MoveEvent>,
+    mut thinking: Query<(Entity, &mut Thinking)>,
+    game: Res<Game>,
+) {
+    for (id, mut thinking) in thinking.iter_mut() {
+        if !thinking.task.is_finished() {
+            if thinking.position != game.position {
+                thinking.stop.store(true, Ordering::Relaxed);
+            }
+            continue;
+        }
+
+        let best = block_on(&mut thinking.task);
+        commands.entity(id).despawn();
+
+        if let Some(m) = best.filter(|_| thinking.position == game.position) {
+            events.send(MoveEvent(m));
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! The game's rules and search as a UCI engine on stdin and stdout, without any window
+
+use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use bevy_project::search::{self, Report, Skill, MATE, MAX_DEPTH};
+use bevy_project::{rules, uci};
+
+/// The search running in the background, stopped by setting its flag
+struct Searching {
+    stop: Arc<AtomicBool>,
+    /// Set under `go infinite` and `go ponder`, keeping back `bestmove` until `stop` or `ponderhit`
+    hold: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+fn main() {
+    let mut position = rules::Position::start();
+    let mut searching = None;
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let line = line.trim();
+        let (command, arguments) = line.split_once(' ').unwrap_or((line, ""));
+
+        match command {
+            "uci" => {
+                println!(
+                    "id name {} {}",
+                    env!("CARGO_PKG_NAME"),
+                    env!("CARGO_PKG_VERSION")
+                );
+                println!("uciok");
+            }
+            "isready" => println!("readyok"),
+            "ucinewgame" => {
+                stop(&mut searching);
+                position = rules::Position::start();
+            }
+            "position" => match uci::parse_position(arguments) {
+                Ok(new_position) => position = new_position,
+                Err(error) => println!("info string {}", error),
+            },
+            "go" => {
+                stop(&mut searching);
+                searching = Some(go(&position, arguments));
+            }
+            "ponderhit" => release(&searching),
+            "stop" => stop(&mut searching),
+            "quit" => break,
+            _ => {}
+        }
+    }
+
+    stop(&mut searching);
+}
+
+fn go(position: &rules::Position, arguments: &str) -> Searching {
+    let limits = uci::parse_go(arguments, position.side);
+    let stop = Arc::new(AtomicBool::new(false));
+    let hold = Arc::new(AtomicBool::new(
+        arguments
+            .split_whitespace()
+            .any(|word| word == "infinite" || word == "ponder"),
+    ));
+
+    let thread = thread::spawn({
+        let position = position.clone();
+        let stop = stop.clone();
+        let hold = hold.clone();
+
+        move || {
+            let report =
+                search::search_with(&position, &limits, Skill::default(), &stop, print_info);
+
+            // NOTE: The loop guards against the thread waking up without being released
+            while hold.load(Ordering::Acquire) {
+                thread::park();
+            }
+
+            match report.best {
+                Some(m) => println!("bestmove {}", uci::move_name(m)),
+                None => println!("bestmove 0000"),
+            }
+        }
+    });
+
+    Searching { stop, hold, thread }
+}
+
+/// Lets the search answer with `bestmove` once it is done
+fn release(searching: &Option<Searching>) {
+    if let Some(searching) = searching {
+        searching.hold.store(false, Ordering::Release);
+        searching.thread.thread().unpark();
+    }
+}
+
+fn stop(searching: &mut Option<Searching>) {
+    release(searching);
+    if let Some(Searching { stop, thread, .. }) = searching.take() {
+        stop.store(true, Ordering::Relaxed);
+        let _ = thread.join();
+    }
+}
+
+fn print_info(report: &Report) {
+    let plies_to_mate = MATE - report.score.abs();
+    let score = if plies_to_mate <= MAX_DEPTH as i32 {
+        format!("mate {}", report.score.signum() * (plies_to_mate + 1) / 2)
+    } else {
+        format!("cp {}", report.score)
+    };
+
+    println!(
+        "info depth {} score {} nodes {} time {} pv {}",
+        report.depth,
+        score,
+        report.nodes,
+        report.time.as_millis(),
+        report.best.map(uci::move_name).unwrap_or_default()
+    );
+}
@@ -1,27 +1,82 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_vector_shapes::prelude::*;
 
 use crate::input::Selection;
+use crate::pieces::Game;
+use bevy_project::rules::{self, PieceColor, PieceType};
 
-pub const SIZE: f32 = 80.0; // NOTE: can we make it relative to the screen height? SIZE = window.height / 13.5
+pub const SIZE: f32 = 80.0; // NOTE: In world units, the camera scales them to the window
 pub const HALF_SIZE: f32 = SIZE * 0.5;
-pub const MAX: u8 = 8;
+/// The part of the world the camera always shows, all of it on a 1080p screen
+pub const VIEW: Vec2 = Vec2::new(SIZE * 24., SIZE * 13.5);
+pub use bevy_project::rules::MAX;
 const ORDER_LAYER: f32 = 0.0;
-
-#[derive(Bundle)]
-struct BoardBundle {
-    model: SceneBundle,
-}
+/// Between the board and the pieces, for the tints showing the game state
+const HIGHLIGHT_LAYER: f32 = ORDER_LAYER + 1.;
+/// Over the frame below the board
+const LABEL_LAYER: f32 = ORDER_LAYER + 1.;
+/// How far the centers of the labels are from the nearest squares' edges
+const LABEL_MARGIN: f32 = SIZE * 0.25;
 
 pub struct BoardPlugin;
 impl Plugin for BoardPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(ClearColor(Color::rgb_u8(57, 31, 33)))
-            .add_systems(Startup, load_sprites)
-            .add_systems(Update, draw_selected);
+            .init_resource::<Orientation>()
+            .add_systems(Startup, (load_sprites, spawn_coordinates))
+            .add_systems(
+                Update,
+                (
+                    place_coordinates.run_if(resource_changed::<Orientation>()),
+                    draw_highlights,
+                    draw_selected,
+                ),
+            );
+    }
+}
+
+/// The color whose side of the board is drawn at the bottom
+#[derive(Resource, Clone, Copy, Default, PartialEq, Debug)]
+pub struct Orientation(pub PieceColor);
+
+impl Orientation {
+    pub fn flip(&mut self) {
+        self.0 = self.0.opposite();
+    }
+
+    /// Where the center of `square` is drawn, at depth `z`
+    pub fn translation(self, square: rules::Square, z: f32) -> Vec3 {
+        let (x, y) = self.view(square);
+        Vec3::new(x as f32 * SIZE, y as f32 * SIZE, z)
+    }
+
+    /// The square drawn under `point`, if it is on the board
+    pub fn square_at(self, point: Vec2) -> Option<rules::Square> {
+        if !inside_board(point.x, point.y) {
+            return None;
+        }
+
+        let pos = square_center(point.x, point.y) / SIZE;
+        Some(self.view((pos.x as u8, pos.y as u8)))
+    }
+
+    /// Turns a square of the board into the one it is drawn on, and back, as flipping twice does nothing
+    fn view(self, (x, y): rules::Square) -> rules::Square {
+        match self.0 {
+            PieceColor::White => (x, y),
+            PieceColor::Black => (MAX - 1 - x, MAX - 1 - y),
+        }
     }
 }
 
+/// A file or rank label next to the board
+#[derive(Component, Clone, Copy)]
+enum Coordinate {
+    File(u8),
+    Rank(u8),
+}
+
 fn load_sprites(mut commands: Commands, asset_server: Res<AssetServer>) {
     let mut choice = true;
     let white_image_path = "ARABIAN CHESS/sprites/board/board_square_white.png";
@@ -45,16 +100,89 @@ fn load_sprites(mut commands: Commands, asset_server: Res<AssetServer>) {
     }
 }
 
+/// The frame below the board with the file letters on it, and the rank numbers on the left
+fn spawn_coordinates(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let path = "ARABIAN CHESS/sprites/nums & letters";
+    let scale = Vec3::new(SIZE / 32., SIZE / 32., 1.);
+
+    commands.spawn(SpriteBundle {
+        texture: asset_server.load("ARABIAN CHESS/sprites/board/board_bottom.png"),
+        transform: Transform::from_xyz(
+            SIZE * (MAX as f32 - 1.) * 0.5,
+            -HALF_SIZE - LABEL_MARGIN,
+            ORDER_LAYER,
+        )
+        .with_scale(Vec3::new(SIZE / 16., SIZE / 16., 1.)),
+        ..default()
+    });
+
+    for i in 0..MAX {
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load(format!("{}/letter_{}.png", path, (b'a' + i) as char)),
+                transform: Transform::from_scale(scale),
+                ..default()
+            },
+            Coordinate::File(i),
+        ));
+
+        // NOTE: The digits only go from 0 to 7, the last rank is written with the default font instead
+        if i + 1 < MAX {
+            commands.spawn((
+                SpriteBundle {
+                    texture: asset_server.load(format!("{}/num_{}.png", path, i + 1)),
+                    transform: Transform::from_scale(scale),
+                    ..default()
+                },
+                Coordinate::Rank(i),
+            ));
+        } else {
+            commands.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        (i + 1).to_string(),
+                        TextStyle {
+                            font_size: HALF_SIZE,
+                            color: Color::rgb_u8(246, 202, 159),
+                            ..default()
+                        },
+                    ),
+                    ..default()
+                },
+                Coordinate::Rank(i),
+            ));
+        }
+    }
+}
+
+/// Puts the labels next to their files and ranks, which swap ends when the board is flipped
+fn place_coordinates(
+    mut labels: Query<(&mut Transform, &Coordinate)>,
+    orientation: Res<Orientation>,
+) {
+    for (mut transform, coordinate) in labels.iter_mut() {
+        let (x, y) = match *coordinate {
+            Coordinate::File(file) => (
+                orientation.translation((file, 0), LABEL_LAYER).x,
+                -HALF_SIZE - LABEL_MARGIN,
+            ),
+            Coordinate::Rank(rank) => (
+                -HALF_SIZE - LABEL_MARGIN,
+                orientation.translation((0, rank), LABEL_LAYER).y,
+            ),
+        };
+        transform.translation = Vec3::new(x, y, LABEL_LAYER);
+    }
+}
+
 pub fn inside_board(x: f32, y: f32) -> bool {
     let half = SIZE * 0.5;
     let min = -half; // NOTE: Same as -> (0.0 * SIZE) - half
     let max = ((MAX as f32 - 1.0) * SIZE) + half;
-    return x > min && x < max && y > min && y < max;
+    x > min && x < max && y > min && y < max
 }
 
 pub fn square_center(x: f32, y: f32) -> Vec2 {
-    return Vec2::new(nearest_center(x), nearest_center(y));
-
     fn nearest_center(axis: f32) -> f32 {
         let mut nearest: u8 = 0;
         let mut smallest_diff: f32 = axis; // NOTE: Same as -> ((0 as f32 * SIZE) - axis_value).abs()
@@ -67,24 +195,143 @@ pub fn square_center(x: f32, y: f32) -> Vec2 {
             }
         }
 
-        return nearest as f32 * SIZE;
+        nearest as f32 * SIZE
+    }
+
+    Vec2::new(nearest_center(x), nearest_center(y))
+}
+
+/// The cursor over the board, seen through the camera and the board's orientation
+#[derive(SystemParam)]
+pub struct BoardCursor<'w, 's> {
+    cameras: Query<'w, 's, (&'static Camera, &'static GlobalTransform)>,
+    windows: Query<'w, 's, &'static Window>,
+    pub orientation: Res<'w, Orientation>,
+}
+
+impl BoardCursor<'_, '_> {
+    /// The point of the board under the cursor
+    pub fn point(&self) -> Option<Vec2> {
+        let cursor_pos = self.windows.single().cursor_position()?;
+        let (camera, camera_transform) = self.cameras.single();
+        camera.viewport_to_world_2d(camera_transform, cursor_pos)
+    }
+
+    /// The square under the cursor, if it is on the board
+    pub fn square(&self) -> Option<rules::Square> {
+        self.orientation.square_at(self.point()?)
+    }
+}
+
+/// Tints the squares of the last move and the one under the cursor, and makes a checked king glow
+fn draw_highlights(mut painter: ShapePainter, game: Res<Game>, cursor: BoardCursor) {
+    let orientation = *cursor.orientation;
+    let square = |painter: &mut ShapePainter, square: rules::Square, color: Color| {
+        painter.set_translation(orientation.translation(square, HIGHLIGHT_LAYER));
+        painter.color = color;
+        painter.rect(Vec2::splat(SIZE));
+    };
+
+    let ply = game.viewed.unwrap_or(game.moves.len());
+    if let Some(m) = ply.checked_sub(1).map(|i| game.moves[i]) {
+        square(&mut painter, m.from, Color::rgba(0.9, 0.8, 0.2, 0.35));
+        square(&mut painter, m.to, Color::rgba(0.9, 0.8, 0.2, 0.5));
+    }
+
+    let shown = game.shown();
+    if let Some(king) = shown
+        .king(shown.side)
+        .filter(|_| shown.in_check(shown.side))
+    {
+        painter.set_translation(orientation.translation(king, HIGHLIGHT_LAYER));
+        painter.color = Color::rgba(1.0, 0.0, 0.0, 0.3);
+        // NOTE: Stacked translucent circles fade out from the center like a glow
+        for radius in [0.5, 0.4, 0.3, 0.2] {
+            painter.circle(SIZE * radius);
+        }
+    }
+
+    if let Some(hovered) = cursor.square() {
+        square(&mut painter, hovered, Color::rgba(1.0, 1.0, 1.0, 0.15));
     }
 }
 
-fn draw_selected(mut painter: ShapePainter, selection: Res<Selection>) {
+fn draw_selected(
+    mut painter: ShapePainter,
+    selection: Res<Selection>,
+    game: Res<Game>,
+    orientation: Res<Orientation>,
+) {
     if selection.from == Vec2::NEG_ONE {
         return;
     }
 
-    let pos = Vec3::new(
-        selection.from.x * SIZE,
-        selection.from.y * SIZE,
-        ORDER_LAYER + 1.,
-    );
-    painter.set_translation(pos);
+    let from = (selection.from.x as u8, selection.from.y as u8);
+    painter.set_translation(orientation.translation(from, HIGHLIGHT_LAYER + 1.));
 
     painter.color = Color::WHITE;
     painter.circle(SIZE * 0.5);
+
+    // NOTE: The four promotions of a pawn share their squares, one hint is enough
+    let moves = rules::legal_moves(&game.position)
+        .into_iter()
+        .filter(|m| m.from == from && m.promotion.is_none_or(|p| p == PieceType::Queen));
+
+    for m in moves {
+        draw_hint(&mut painter, &game.position, *orientation, m);
+    }
+}
+
+/// Marks the destination of a legal move: a dot for quiet moves, a ring for captures,
+/// a square for castling, a diamond for en passant and an arrow for promotions
+fn draw_hint(
+    painter: &mut ShapePainter,
+    position: &rules::Position,
+    orientation: Orientation,
+    m: rules::Move,
+) {
+    let quiet = Color::rgba(0.0, 0.0, 0.0, 0.35);
+    let special = Color::rgba(0.95, 0.75, 0.2, 0.8);
+
+    painter.set_translation(orientation.translation(m.to, HIGHLIGHT_LAYER + 2.));
+    painter.thickness = SIZE * 0.08;
+    painter.hollow = true;
+
+    if position.is_castling(m) {
+        painter.color = special;
+        painter.rect(Vec2::splat(SIZE * 0.7));
+    } else if position.is_en_passant(m) {
+        painter.color = special;
+        painter.ngon(4., SIZE * 0.4);
+    } else if m.promotion.is_some() {
+        if position.piece_at(m.to).is_some() {
+            painter.color = quiet;
+            painter.circle(SIZE * 0.45);
+        }
+
+        // NOTE: The arrow points the way the pawn walks, down the screen for the side drawn at the top
+        if position.side != orientation.0 {
+            painter.rotate_z(std::f32::consts::PI);
+        }
+        let size = SIZE * 0.25;
+        painter.hollow = false;
+        painter.color = special;
+        painter.triangle(
+            Vec2::new(0., size),
+            Vec2::new(-size, -size),
+            Vec2::new(size, -size),
+        );
+        painter.set_rotation(Quat::IDENTITY);
+    } else if position.piece_at(m.to).is_some() {
+        painter.color = quiet;
+        painter.circle(SIZE * 0.45);
+    } else {
+        painter.hollow = false;
+        painter.color = quiet;
+        painter.circle(SIZE * 0.15);
+    }
+
+    painter.hollow = false;
 }
 
 //
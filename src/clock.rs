@@ -0,0 +1,164 @@
+//! Chess clocks with increment and delay time controls
+
+use std::time::Duration;
+
+use crate::rules::PieceColor;
+
+/// The time given back to a player for each of their moves
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Bonus {
+    #[default]
+    None,
+    /// Fischer increment, added after every move
+    Increment(Duration),
+    /// The clock only starts running once the delay has passed
+    SimpleDelay(Duration),
+    /// The time used is given back after the move, up to the delay
+    Bronstein(Duration),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TimeControl {
+    pub base: Duration,
+    pub bonus: Bonus,
+}
+
+impl TimeControl {
+    pub fn new(minutes: u64, seconds: u64) -> Self {
+        Self {
+            base: Duration::from_secs(minutes * 60),
+            bonus: if seconds == 0 {
+                Bonus::None
+            } else {
+                Bonus::Increment(Duration::from_secs(seconds))
+            },
+        }
+    }
+
+    pub fn bullet() -> Self {
+        Self::new(1, 0)
+    }
+
+    pub fn blitz() -> Self {
+        Self::new(3, 2)
+    }
+
+    pub fn rapid() -> Self {
+        Self::new(10, 5)
+    }
+
+    pub fn classical() -> Self {
+        Self::new(90, 30)
+    }
+}
+
+/// Reads a preset name or `MINUTES+SECONDS`, the seconds being an increment,
+/// a simple delay with a `d` suffix or a Bronstein delay with a `b` suffix
+pub fn parse(text: &str) -> Result<TimeControl, String> {
+    let invalid = || format!("invalid time control '{}'", text);
+
+    match text.trim().to_lowercase().as_str() {
+        "bullet" => return Ok(TimeControl::bullet()),
+        "blitz" => return Ok(TimeControl::blitz()),
+        "rapid" => return Ok(TimeControl::rapid()),
+        "classical" => return Ok(TimeControl::classical()),
+        _ => {}
+    }
+
+    let (minutes, seconds) = text.trim().split_once('+').unwrap_or((text.trim(), "0"));
+    let (seconds, suffix) = match seconds.strip_suffix(['d', 'b']) {
+        Some(rest) => (rest, seconds.chars().last()),
+        None => (seconds, None),
+    };
+
+    let minutes: u64 = minutes.parse().map_err(|_| invalid())?;
+    let seconds: u64 = seconds.parse().map_err(|_| invalid())?;
+    if minutes == 0 {
+        return Err(invalid());
+    }
+
+    let mut control = TimeControl::new(minutes, seconds);
+    let bonus = Duration::from_secs(seconds);
+    match suffix {
+        Some('d') => control.bonus = Bonus::SimpleDelay(bonus),
+        Some('b') => control.bonus = Bonus::Bronstein(bonus),
+        _ => {}
+    }
+
+    Ok(control)
+}
+
+/// Both players' time, only the clock of `side` runs
+#[derive(Clone, PartialEq, Debug)]
+pub struct Clock {
+    pub control: TimeControl,
+    pub side: PieceColor,
+    // NOTE: For `side` this is the time left when its turn started
+    remaining: [Duration; 2],
+    spent: Duration,
+    flagged: Option<PieceColor>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl, side: PieceColor) -> Self {
+        Self {
+            control,
+            side,
+            remaining: [control.base; 2],
+            spent: Duration::ZERO,
+            flagged: None,
+        }
+    }
+
+    pub fn remaining(&self, color: PieceColor) -> Duration {
+        let start = self.remaining[color as usize];
+        if color == self.side {
+            start.saturating_sub(self.charged())
+        } else {
+            start
+        }
+    }
+
+    /// The player whose time ran out
+    pub fn flagged(&self) -> Option<PieceColor> {
+        self.flagged
+    }
+
+    /// Runs the clock of `side` for `delta`, returning the player whose flag falls
+    pub fn tick(&mut self, delta: Duration) -> Option<PieceColor> {
+        if self.flagged.is_none() {
+            self.spent += delta;
+            if self.remaining(self.side).is_zero() {
+                self.flagged = Some(self.side);
+            }
+        }
+
+        self.flagged
+    }
+
+    /// Ends the turn of `side`, giving it its bonus and starting the other clock
+    pub fn press(&mut self) {
+        if self.flagged.is_some() {
+            return;
+        }
+
+        let mut left = self.remaining(self.side);
+        match self.control.bonus {
+            Bonus::Increment(increment) => left += increment,
+            Bonus::Bronstein(delay) => left += self.spent.min(delay),
+            Bonus::SimpleDelay(_) | Bonus::None => {}
+        }
+
+        self.remaining[self.side as usize] = left;
+        self.side = self.side.opposite();
+        self.spent = Duration::ZERO;
+    }
+
+    /// The time taken off the clock of `side` this turn
+    fn charged(&self) -> Duration {
+        match self.control.bonus {
+            Bonus::SimpleDelay(delay) => self.spent.saturating_sub(delay),
+            _ => self.spent,
+        }
+    }
+}
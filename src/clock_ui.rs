@@ -0,0 +1,71 @@
+use super::*;
+
+/// The clocks of a timed game, games without it are untimed
+#[derive(Resource)]
+pub struct Clocks(pub clock::Clock);
+
+/// The clocks when each ply started, kept after undoing for redoing
+#[derive(Resource, Default)]
+struct ClockHistory {
+    /// The ply the clocks are running for, with the move leading to it
+    current: (usize, Option<rules::Move>),
+    saved: Vec<((usize, Option<rules::Move>), clock::Clock)>,
+}
+
+pub struct ClockUiPlugin;
+impl Plugin for ClockUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClockHistory>().add_systems(
+            Update,
+            (
+                start_history.run_if(resource_added::<Clocks>()),
+                follow_game.run_if(resource_changed::<Game>()),
+                run_clock
+                    .run_if(in_state(GameState::Playing).or_else(in_state(GameState::Promotion))),
+            )
+                .chain()
+                .run_if(resource_exists::<Clocks>()),
+        );
+    }
+}
+
+fn start_history(mut history: ResMut<ClockHistory>, clocks: Res<Clocks>, game: Res<Game>) {
+    history.current = (game.moves.len(), game.moves.last().copied());
+    history.saved = vec![(history.current, clocks.0.clone())];
+}
+
+/// Presses the clock for the moves played, and puts back the clocks of the plies undone or redone to
+fn follow_game(mut clocks: ResMut<Clocks>, mut history: ResMut<ClockHistory>, game: Res<Game>) {
+    let ply = (game.moves.len(), game.moves.last().copied());
+    let previous = std::mem::replace(&mut history.current, ply);
+    if ply == previous {
+        return;
+    }
+
+    if let Some((_, saved)) = history.saved.iter().find(|(at, _)| *at == ply) {
+        clocks.0 = saved.clone();
+        return;
+    }
+
+    // NOTE: Going back before the clocks started leaves them as they are
+    if ply.0 < previous.0 {
+        return;
+    }
+
+    // NOTE: A new move replaces the line that could be redone
+    history.saved.retain(|((at, _), _)| *at < ply.0);
+    for _ in previous.0..ply.0 {
+        clocks.0.press();
+    }
+    history.saved.push((ply, clocks.0.clone()));
+}
+
+fn run_clock(
+    mut clocks: ResMut<Clocks>,
+    mut next_state: ResMut<NextState<GameState>>,
+    time: Res<Time>,
+) {
+    if clocks.0.tick(time.delta()).is_some() {
+        next_state.set(GameState::GameOver);
+    }
+}
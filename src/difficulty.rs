@@ -0,0 +1,82 @@
+//! Named strengths for the computer player
+
+use std::time::Duration;
+
+use crate::eval::Personality;
+use crate::search::{Limits, Skill, MAX_DEPTH};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Difficulty {
+    Beginner,
+    Casual,
+    #[default]
+    Club,
+    Expert,
+    Master,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Beginner,
+        Difficulty::Casual,
+        Difficulty::Club,
+        Difficulty::Expert,
+        Difficulty::Master,
+    ];
+
+    /// A rough rating of the strength, only meant to compare the levels with each other
+    pub fn elo(self) -> u32 {
+        match self {
+            Difficulty::Beginner => 600,
+            Difficulty::Casual => 1000,
+            Difficulty::Club => 1400,
+            Difficulty::Expert => 1800,
+            Difficulty::Master => 2100,
+        }
+    }
+
+    pub fn limits(self) -> Limits {
+        let (depth, nodes, time) = match self {
+            Difficulty::Beginner => (1, Some(2_000), None),
+            Difficulty::Casual => (2, Some(20_000), None),
+            Difficulty::Club => (3, Some(200_000), None),
+            Difficulty::Expert => (4, None, Some(3)),
+            Difficulty::Master => (MAX_DEPTH, None, Some(5)),
+        };
+
+        Limits {
+            depth,
+            time: time.map(Duration::from_secs),
+            nodes,
+        }
+    }
+
+    /// How far the weaker levels stray from their best moves
+    pub fn skill(self, personality: Personality, seed: u64) -> Skill {
+        let (noise, mistakes) = match self {
+            Difficulty::Beginner => (200, 30),
+            Difficulty::Casual => (100, 15),
+            Difficulty::Club => (40, 5),
+            Difficulty::Expert => (10, 0),
+            Difficulty::Master => (0, 0),
+        };
+
+        Skill {
+            noise,
+            mistakes,
+            personality,
+            seed,
+        }
+    }
+}
+
+impl std::str::FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| format!("{:?}", difficulty).eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown difficulty '{}'", name))
+    }
+}
@@ -0,0 +1,97 @@
+use super::*;
+use bevy::tasks::AsyncComputeTaskPool;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// An external UCI engine playing `color`, asked for its moves on its turns
+#[derive(Resource)]
+pub struct EnginePlayer {
+    pub color: PieceColor,
+    /// `None` once the engine failed, the human then plays on for it
+    pub engine: Arc<Mutex<Option<uci::Engine>>>,
+    /// The time to think in untimed games
+    pub move_time: Duration,
+}
+
+impl EnginePlayer {
+    pub fn new(color: PieceColor, engine: uci::Engine, move_time: Duration) -> Self {
+        Self {
+            color,
+            engine: Arc::new(Mutex::new(Some(engine))),
+            move_time,
+        }
+    }
+
+    /// Whether the engine still plays, which is assumed while it is thinking
+    pub fn running(&self) -> bool {
+        self.engine
+            .try_lock()
+            .map_or(true, |engine| engine.is_some())
+    }
+}
+
+pub struct EnginePlugin;
+impl Plugin for EnginePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            ask_engine
+                .run_if(in_state(GameState::Playing))
+                .run_if(resource_exists::<EnginePlayer>()),
+        );
+    }
+}
+
+/// Starts the engine thinking, its answer is played by `AiPlugin` like the built-in search
+fn ask_engine(
+    mut commands: Commands,
+    player: Res<EnginePlayer>,
+    game: Res<Game>,
+    clocks: Option<Res<Clocks>>,
+    thinking: Query<&Thinking>,
+) {
+    if player.color != game.position.side || !thinking.is_empty() || !player.running() {
+        return;
+    }
+
+    let go = match clocks {
+        Some(clocks) => {
+            let increment = match clocks.0.control.bonus {
+                clock::Bonus::Increment(increment) => increment,
+                _ => Duration::ZERO,
+            };
+
+            uci::Go::Clocks {
+                white: clocks.0.remaining(PieceColor::White),
+                black: clocks.0.remaining(PieceColor::Black),
+                white_increment: increment,
+                black_increment: increment,
+            }
+        }
+        None => uci::Go::MoveTime(player.move_time),
+    };
+
+    let engine = player.engine.clone();
+    let start = game.start.clone();
+    let moves = game.moves.clone();
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let mut engine = engine.lock().ok()?;
+        match engine.as_mut()?.best_move(&start, &moves, go) {
+            Ok(m) => Some(m),
+            Err(error) => {
+                eprintln!(
+                    "The engine gave no move, its moves are left to you: {}",
+                    error
+                );
+                *engine = None;
+                None
+            }
+        }
+    });
+
+    commands.spawn(Thinking {
+        position: game.position.clone(),
+        task,
+        stop: Default::default(),
+    });
+}
@@ -0,0 +1,177 @@
+//! Static evaluation of a `Position` from material and piece-square tables
+
+use crate::rules::*;
+
+// NOTE: Tables from the Simplified Evaluation Function, written from White's side with rank 8 first
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const KING_TABLE: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+/// The material value of `my_type` in centipawns
+pub fn value(my_type: PieceType) -> i32 {
+    match my_type {
+        PieceType::King => 0,
+        PieceType::Queen => 900,
+        PieceType::Rook => 500,
+        PieceType::Bishop => 330,
+        PieceType::Knight => 320,
+        PieceType::Pawn => 100,
+    }
+}
+
+/// The bonus for a piece standing on `square`, from the point of view of its owner
+pub fn square_bonus(color: PieceColor, my_type: PieceType, (x, y): Square) -> i32 {
+    let table = match my_type {
+        PieceType::King => &KING_TABLE,
+        PieceType::Queen => &QUEEN_TABLE,
+        PieceType::Bishop => &BISHOP_TABLE,
+        PieceType::Knight => &KNIGHT_TABLE,
+        PieceType::Rook => &ROOK_TABLE,
+        PieceType::Pawn => &PAWN_TABLE,
+    };
+    let row = match color {
+        PieceColor::White => MAX - 1 - y,
+        PieceColor::Black => y,
+    };
+
+    table[(row * MAX + x) as usize]
+}
+
+/// How the evaluation weighs material against piece placement and attacking the king
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Personality {
+    #[default]
+    Balanced,
+    /// Brings its pieces close to the enemy king, even for some material
+    Aggressive,
+    /// Favours well placed pieces and a safe king
+    Positional,
+}
+
+impl Personality {
+    pub const ALL: [Personality; 3] = [
+        Personality::Balanced,
+        Personality::Aggressive,
+        Personality::Positional,
+    ];
+
+    /// The percentages given to material, piece-square bonuses and king attack
+    fn weights(self) -> (i32, i32, i32) {
+        match self {
+            Personality::Balanced => (100, 100, 0),
+            Personality::Aggressive => (90, 80, 150),
+            Personality::Positional => (100, 160, 0),
+        }
+    }
+}
+
+impl std::str::FromStr for Personality {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Personality::ALL
+            .into_iter()
+            .find(|personality| format!("{:?}", personality).eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown personality '{}'", name))
+    }
+}
+
+/// The score of `position` in centipawns for the side to move
+pub fn evaluate(position: &Position) -> i32 {
+    evaluate_with(position, Personality::Balanced)
+}
+
+pub fn evaluate_with(position: &Position, personality: Personality) -> i32 {
+    let (material, placement, attack) = personality.weights();
+
+    position
+        .pieces()
+        .map(|(square, color, my_type)| {
+            let mut score =
+                value(my_type) * material + square_bonus(color, my_type, square) * placement;
+
+            // NOTE: Up to 20 centipawns at full weight for each piece next to the enemy king, none for pawns and kings
+            if attack != 0 && !matches!(my_type, PieceType::King | PieceType::Pawn) {
+                if let Some(king) = position.king(color.opposite()) {
+                    let distance = square.0.abs_diff(king.0).max(square.1.abs_diff(king.1)) as i32;
+                    score += (MAX as i32 - 1 - distance) * 20 / (MAX as i32 - 2) * attack;
+                }
+            }
+
+            if color == position.side {
+                score
+            } else {
+                -score
+            }
+        })
+        .sum::<i32>()
+        / 100
+}
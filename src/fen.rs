@@ -0,0 +1,224 @@
+//! Forsyth–Edwards Notation for `Position`
+
+use crate::rules::*;
+
+pub const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+pub fn parse(fen: &str) -> Result<Position, String> {
+    let mut fields = fen.split_whitespace();
+    let mut position = Position::empty();
+
+    let placement = fields.next().ok_or("missing piece placement")?;
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != MAX as usize {
+        return Err(format!("expected {} ranks, found {}", MAX, ranks.len()));
+    }
+
+    // NOTE: Ranks are listed from the last one down to the first
+    for (rank, y) in ranks.into_iter().zip((0..MAX).rev()) {
+        let mut x = 0;
+        for c in rank.chars() {
+            if let Some(empty) = c.to_digit(10) {
+                if !(1..=MAX as u32).contains(&empty) {
+                    return Err(format!("invalid number of empty squares '{}'", c));
+                }
+                // NOTE: Checked before adding, a long run of digits would overflow `x`
+                if x as u32 + empty > MAX as u32 {
+                    return Err(format!("rank {} is too long", y + 1));
+                }
+                x += empty as u8;
+                continue;
+            }
+
+            if x >= MAX {
+                return Err(format!("rank {} is too long", y + 1));
+            }
+            position.set((x, y), Some(piece_from_char(c)?));
+            x += 1;
+        }
+
+        if x != MAX {
+            return Err(format!("rank {} does not have {} squares", y + 1, MAX));
+        }
+    }
+
+    position.side = match fields.next() {
+        Some("w") | None => PieceColor::White,
+        Some("b") => PieceColor::Black,
+        Some(other) => return Err(format!("invalid side to move '{}'", other)),
+    };
+
+    for c in fields.next().unwrap_or("-").chars() {
+        match c {
+            'K' => position.castling.white_king_side = true,
+            'Q' => position.castling.white_queen_side = true,
+            'k' => position.castling.black_king_side = true,
+            'q' => position.castling.black_queen_side = true,
+            '-' => {}
+            _ => return Err(format!("invalid castling right '{}'", c)),
+        }
+    }
+
+    position.en_passant = match fields.next() {
+        Some("-") | None => None,
+        Some(name) => {
+            let square = parse_square(name)?;
+
+            // NOTE: The pawn that just moved two squares stands right past the one it skipped
+            let (rank, pawn_rank) = match position.side {
+                PieceColor::White => (MAX - 3, MAX - 4),
+                PieceColor::Black => (2, 3),
+            };
+            let pawn = position.piece_at((square.0, pawn_rank));
+            if square.1 != rank
+                || position.piece_at(square).is_some()
+                || pawn != Some((position.side.opposite(), PieceType::Pawn))
+            {
+                return Err(format!("invalid en passant square '{}'", name));
+            }
+            Some(square)
+        }
+    };
+
+    if let Some(clock) = fields.next() {
+        position.halfmove_clock = clock
+            .parse()
+            .map_err(|_| format!("invalid halfmove clock '{}'", clock))?;
+    }
+    if let Some(number) = fields.next() {
+        position.fullmove_number = number
+            .parse()
+            .map_err(|_| format!("invalid fullmove number '{}'", number))?;
+    }
+
+    if fields.next().is_some() {
+        return Err("too many fields".to_string());
+    }
+    for color in [PieceColor::White, PieceColor::Black] {
+        if position.king(color).is_none() {
+            return Err(format!("{:?} has no king", color));
+        }
+    }
+    if position.in_check(position.side.opposite()) {
+        return Err(format!(
+            "{:?} is in check but not to move",
+            position.side.opposite()
+        ));
+    }
+    if position
+        .pieces()
+        .any(|((_, y), _, my_type)| my_type == PieceType::Pawn && (y == 0 || y == MAX - 1))
+    {
+        return Err("pawn on the first or last rank".to_string());
+    }
+
+    Ok(position)
+}
+
+pub fn write(position: &Position) -> String {
+    let mut placement = String::new();
+    for y in (0..MAX).rev() {
+        let mut empty = 0;
+        for x in 0..MAX {
+            let Some(piece) = position.piece_at((x, y)) else {
+                empty += 1;
+                continue;
+            };
+
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+                empty = 0;
+            }
+            placement.push(piece_to_char(piece));
+        }
+
+        if empty > 0 {
+            placement.push_str(&empty.to_string());
+        }
+        if y > 0 {
+            placement.push('/');
+        }
+    }
+
+    let side = match position.side {
+        PieceColor::White => "w",
+        PieceColor::Black => "b",
+    };
+
+    let rights = [
+        (position.castling.white_king_side, 'K'),
+        (position.castling.white_queen_side, 'Q'),
+        (position.castling.black_king_side, 'k'),
+        (position.castling.black_queen_side, 'q'),
+    ];
+    let mut castling: String = rights
+        .into_iter()
+        .filter(|(right, _)| *right)
+        .map(|(_, c)| c)
+        .collect();
+    if castling.is_empty() {
+        castling.push('-');
+    }
+
+    let en_passant = position.en_passant.map_or("-".to_string(), square_name);
+
+    format!(
+        "{} {} {} {} {} {}",
+        placement, side, castling, en_passant, position.halfmove_clock, position.fullmove_number
+    )
+}
+
+pub fn parse_square(square: &str) -> Result<Square, String> {
+    let mut chars = square.chars();
+    let (Some(file), Some(rank), None) = (chars.next(), chars.next(), chars.next()) else {
+        return Err(format!("invalid square '{}'", square));
+    };
+
+    let x = (file as u32).wrapping_sub('a' as u32);
+    let y = (rank as u32).wrapping_sub('1' as u32);
+    if x >= MAX as u32 || y >= MAX as u32 {
+        return Err(format!("invalid square '{}'", square));
+    }
+
+    Ok((x as u8, y as u8))
+}
+
+pub fn square_name(square: Square) -> String {
+    format!("{}{}", (b'a' + square.0) as char, square.1 + 1)
+}
+
+fn piece_from_char(c: char) -> Result<(PieceColor, PieceType), String> {
+    let color = if c.is_ascii_uppercase() {
+        PieceColor::White
+    } else {
+        PieceColor::Black
+    };
+
+    let my_type = match c.to_ascii_lowercase() {
+        'k' => PieceType::King,
+        'q' => PieceType::Queen,
+        'b' => PieceType::Bishop,
+        'n' => PieceType::Knight,
+        'r' => PieceType::Rook,
+        'p' => PieceType::Pawn,
+        _ => return Err(format!("invalid piece '{}'", c)),
+    };
+
+    Ok((color, my_type))
+}
+
+fn piece_to_char((color, my_type): (PieceColor, PieceType)) -> char {
+    let c = match my_type {
+        PieceType::King => 'k',
+        PieceType::Queen => 'q',
+        PieceType::Bishop => 'b',
+        PieceType::Knight => 'n',
+        PieceType::Rook => 'r',
+        PieceType::Pawn => 'p',
+    };
+
+    match color {
+        PieceColor::White => c.to_ascii_uppercase(),
+        PieceColor::Black => c,
+    }
+}
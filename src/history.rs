@@ -0,0 +1,225 @@
+use super::*;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+
+const LINE_HEIGHT: f32 = 24.0;
+const CURRENT_COLOR: Color = Color::rgb(0.45, 0.3, 0.2);
+
+/// The side panel listing the moves played so far
+#[derive(Component)]
+struct HistoryPanel;
+
+/// The scrolled column of move pairs inside the `HistoryPanel`
+#[derive(Component, Default)]
+struct HistoryList {
+    position: f32,
+    /// Whether the list stays scrolled down to the latest move
+    follow: bool,
+}
+
+/// A move in the `HistoryPanel`, holding the number of moves played once it is made
+#[derive(Component)]
+struct HistoryEntry(usize);
+
+pub struct HistoryPlugin;
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_history_panel).add_systems(
+            Update,
+            (
+                list_moves.run_if(resource_changed::<Game>()),
+                view_move.run_if(not(in_state(GameState::Promotion))),
+                return_to_latest,
+                scroll_history,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn spawn_history_panel(mut commands: Commands) {
+    let panel = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(10.0),
+            right: Val::Percent(3.0),
+            width: Val::Percent(16.0),
+            height: Val::Percent(75.0),
+            padding: UiRect::all(Val::Px(HALF_SIZE * 0.25)),
+            overflow: Overflow::clip_y(),
+            ..default()
+        },
+        background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+        ..default()
+    };
+    let list = NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Column,
+            width: Val::Percent(100.0),
+            ..default()
+        },
+        ..default()
+    };
+
+    commands
+        .spawn((panel, HistoryPanel))
+        .with_children(|parent| {
+            parent.spawn((list, HistoryList::default()));
+        });
+}
+
+/// Rebuilds the numbered move pairs, highlighting the move leading to the shown position
+fn list_moves(
+    mut commands: Commands,
+    mut lists: Query<(Entity, &mut HistoryList)>,
+    game: Res<Game>,
+) {
+    let font_size = HALF_SIZE * 0.6;
+    let shown = game.viewed.unwrap_or(game.moves.len());
+
+    let mut rows = Vec::new();
+    let mut position = game.start.clone();
+    for (i, m) in game.moves.iter().enumerate() {
+        // NOTE: A game set up with Black to move starts its first row with an empty White move
+        if position.side == PieceColor::White || i == 0 {
+            rows.push((position.fullmove_number, Vec::new()));
+            if position.side == PieceColor::Black {
+                rows.last_mut().unwrap().1.push(None);
+            }
+        }
+
+        let san = san::display(&position, *m);
+        rows.last_mut().unwrap().1.push(Some((i + 1, san)));
+        position = rules::apply(&position, *m);
+    }
+
+    for (id, mut list) in lists.iter_mut() {
+        // NOTE: A new move scrolls back down to it once the list is laid out again
+        if game.at_latest() {
+            list.follow = true;
+        }
+
+        commands.entity(id).despawn_descendants();
+        commands.entity(id).with_children(|parent| {
+            for (number, entries) in &rows {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            height: Val::Px(LINE_HEIGHT.max(font_size * 1.5)),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(entry_text(format!("{}.", number), font_size, 20.0));
+
+                        for entry in entries {
+                            let Some((ply, san)) = entry else {
+                                parent.spawn(entry_text("...".to_string(), font_size, 40.0));
+                                continue;
+                            };
+
+                            parent
+                                .spawn((
+                                    ButtonBundle {
+                                        style: Style {
+                                            width: Val::Percent(40.0),
+                                            height: Val::Percent(100.0),
+                                            align_items: AlignItems::Center,
+                                            ..default()
+                                        },
+                                        background_color: if *ply == shown {
+                                            CURRENT_COLOR.into()
+                                        } else {
+                                            Color::NONE.into()
+                                        },
+                                        ..default()
+                                    },
+                                    HistoryEntry(*ply),
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn(entry_text(san.clone(), font_size, 100.0));
+                                });
+                        }
+                    });
+            }
+        });
+    }
+}
+
+fn entry_text(value: String, font_size: f32, width: f32) -> TextBundle {
+    TextBundle {
+        text: Text::from_section(
+            value,
+            TextStyle {
+                font_size,
+                ..default()
+            },
+        ),
+        style: Style {
+            width: Val::Percent(width),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+/// Shows the position after the pressed move, pressing the last one returns to the game
+fn view_move(
+    mut game: ResMut<Game>,
+    mut selection: ResMut<Selection>,
+    entries: Query<(&Interaction, &HistoryEntry), Changed<Interaction>>,
+) {
+    for (interaction, entry) in entries.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        game.viewed = (entry.0 != game.moves.len()).then_some(entry.0);
+        selection.from = Vec2::NEG_ONE;
+        selection.to = Vec2::NEG_ONE;
+    }
+}
+
+fn return_to_latest(mut game: ResMut<Game>, keyboard_input: Res<Input<KeyCode>>) {
+    if keyboard_input.just_pressed(KeyCode::End) && !game.at_latest() {
+        game.viewed = None;
+    }
+}
+
+fn scroll_history(
+    mut mouse_wheel: EventReader<MouseWheel>,
+    mut lists: Query<(&mut HistoryList, &mut Style, &Parent, &Node)>,
+    panels: Query<&Node, With<HistoryPanel>>,
+) {
+    let scrolled: f32 = mouse_wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        })
+        .sum();
+
+    for (mut list, mut style, parent, node) in lists.iter_mut() {
+        let Ok(panel) = panels.get(parent.get()) else {
+            continue;
+        };
+
+        // NOTE: The panel padding is left out of the visible height
+        let visible = panel.size().y - HALF_SIZE * 0.5;
+        let hidden = (node.size().y - visible).max(0.0);
+        if scrolled != 0.0 {
+            list.follow = false;
+        }
+
+        let position = if list.follow {
+            -hidden
+        } else {
+            (list.position + scrolled).clamp(-hidden, 0.0)
+        };
+        if position != list.position {
+            list.position = position;
+            style.top = Val::Px(position);
+        }
+    }
+}
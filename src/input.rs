@@ -1,9 +1,12 @@
 use super::*;
+use std::path::PathBuf;
 
 #[derive(Default, Resource)]
 pub struct Selection {
     pub from: Vec2,
     pub to: Vec2,
+    /// Whether the piece on `from` follows the cursor until the button is released
+    pub dragging: bool,
 }
 
 pub struct InputPlugin;
@@ -12,11 +15,40 @@ impl Plugin for InputPlugin {
         app.insert_resource(Selection {
             from: Vec2::NEG_ONE,
             to: Vec2::NEG_ONE,
+            dragging: false,
         })
-        .insert_resource(TurnManager::default())
-        .add_systems(Startup, spawn_turn_text)
-        .add_systems(Update, (click_input, bevy::window::close_on_esc))
-        .add_systems(FixedUpdate, update_turn_text);
+        .init_resource::<TurnManager>()
+        .init_resource::<MoveInput>()
+        .add_systems(
+            Update,
+            (
+                (click_input, drag_piece)
+                    .chain()
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(viewing_latest)
+                    .run_if(human_turn),
+                type_move
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(viewing_latest)
+                    .run_if(human_turn),
+                update_move_input_text,
+                history_input.run_if(not(in_state(GameState::Menu))),
+                export_fen,
+                flip_board,
+                save_pgn,
+                bevy::window::close_on_esc,
+            ),
+        );
+    }
+}
+
+/// Where `Ctrl+S` saves the game as PGN
+#[derive(Resource)]
+pub struct GameFile(pub PathBuf);
+
+impl Default for GameFile {
+    fn default() -> Self {
+        Self("game.pgn".into())
     }
 }
 
@@ -24,71 +56,37 @@ impl Plugin for InputPlugin {
 pub struct TurnManager(PieceColor);
 
 impl TurnManager {
+    pub fn new(color: PieceColor) -> Self {
+        Self(color)
+    }
     pub fn next_turn(&mut self) {
-        match self.0 {
-            PieceColor::Black => self.0 = PieceColor::White,
-            PieceColor::White => self.0 = PieceColor::Black,
-        }
+        self.0 = self.0.opposite();
+    }
+    pub fn color(&self) -> PieceColor {
+        self.0
     }
     pub fn same_color(&self, color: PieceColor) -> bool {
         self.0 == color
     }
 }
 
-#[derive(Component)]
-struct TurnText;
-
-fn spawn_turn_text(mut commands: Commands) {
-    commands.spawn((
-        TextBundle {
-            text: Text {
-                sections: vec![TextSection::default()],
-                alignment: TextAlignment::Center,
-                ..default()
-            },
-            style: Style {
-                position_type: PositionType::Absolute,
-                align_self: AlignSelf::Center,
-                align_content: AlignContent::Center,
-                align_items: AlignItems::Center,
-
-                bottom: Val::Percent(90.0),
-                right: Val::Percent(35.0),
-                left: Val::Percent(35.0),
-
-                width: Val::Percent(30.0),
-                ..default()
-            },
-            ..default()
-        },
-        TurnText,
-    ));
-}
-
-fn update_turn_text(
-    mut query: Query<&mut Text, With<TurnText>>,
-    windows: Query<&Window>,
-    turn_manager: Res<TurnManager>,
-) {
-    if !turn_manager.is_changed() {
-        return;
-    }
-
-    for mut text in query.iter_mut() {
-        text.sections[0].value = format!("{:?} player turn", turn_manager.0);
-        text.sections[0].style.font_size = windows.single().resolution.width() * 0.032;
-    }
+/// The move being typed in SAN, played on `Enter`
+#[derive(Resource, Default)]
+struct MoveInput {
+    text: String,
+    /// An error or notice shown instead of the typed text until typing resumes
+    message: Option<String>,
 }
 
 fn click_input(
     mut selection: ResMut<Selection>,
     turn_manager: Res<TurnManager>,
+    game: Res<Game>,
     mouse_button_input: Res<Input<MouseButton>>,
-    cameras: Query<(&Camera, &GlobalTransform)>,
-    windows: Query<&Window>,
+    cursor: BoardCursor,
     pieces: Query<&Piece>,
 ) {
-    if mouse_button_input.just_pressed(MouseButton::Right) {
+    if mouse_button_input.just_pressed(MouseButton::Right) && !selection.dragging {
         selection.from = Vec2::NEG_ONE;
         selection.to = Vec2::NEG_ONE;
         return;
@@ -98,118 +96,215 @@ fn click_input(
         return;
     }
 
-    let Some(cursor_pos) = windows.single().cursor_position() else {
+    let Some(square) = cursor.square() else {
         return;
     };
 
-    let (camera, camera_transform) = cameras.single();
-    let Some(point) = camera.viewport_to_world_2d(camera_transform, cursor_pos) else {
+    let pos = Vec2::new(square.0 as f32, square.1 as f32);
+
+    if selection.from != Vec2::NEG_ONE
+        && pos != selection.from
+        && valid_path(
+            (selection.from.x as u8, selection.from.y as u8),
+            square,
+            &game.position,
+        )
+    {
+        selection.to = pos;
+        return;
+    }
+
+    // NOTE: Pressing on one of the player's pieces selects it and picks it up for dragging
+    for piece in pieces.iter() {
+        if (piece.x, piece.y) == square && turn_manager.same_color(piece.color) {
+            selection.from = pos;
+            selection.dragging = true;
+            break;
+        }
+    }
+}
+
+/// Moves the dragged piece with the cursor and drops it once the button is released,
+/// back on its own square when the move is illegal
+fn drag_piece(
+    mut selection: ResMut<Selection>,
+    game: Res<Game>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    cursor: BoardCursor,
+    mut pieces: Query<(&Piece, &mut Transform)>,
+) {
+    if !selection.dragging {
+        return;
+    }
+
+    let from = (selection.from.x as u8, selection.from.y as u8);
+    let Some((_, mut transform)) = pieces
+        .iter_mut()
+        .find(|(piece, _)| (piece.x, piece.y) == from)
+    else {
+        selection.dragging = false;
         return;
     };
 
-    if !inside_board(point.x, point.y) {
+    if mouse_button_input.pressed(MouseButton::Left) {
+        if let Some(point) = cursor.point() {
+            transform.translation = point.extend(DRAGGED_LAYER);
+        }
         return;
     }
 
-    let pos = square_center(point.x, point.y) / SIZE;
+    selection.dragging = false;
+
+    // NOTE: Dropping the piece back on its square keeps it selected for a click on the destination
+    let to = match cursor.square() {
+        Some(to) if valid_path(from, to, &game.position) => {
+            selection.to = Vec2::new(to.0 as f32, to.1 as f32);
+            to
+        }
+        _ => from,
+    };
+
+    transform.translation = piece_translation(&cursor.orientation, to);
+}
+
+fn valid_path(from: (u8, u8), to: (u8, u8), position: &rules::Position) -> bool {
+    rules::legal_moves(position)
+        .iter()
+        .any(|m| m.from == from && m.to == to)
+}
+
+fn type_move(
+    mut characters: EventReader<ReceivedCharacter>,
+    mut events: EventWriter<MoveEvent>,
+    mut input: ResMut<MoveInput>,
+    keyboard_input: Res<Input<KeyCode>>,
+    game: Res<Game>,
+) {
+    let typed: Vec<char> = characters.read().map(|event| event.char).collect();
+
+    // NOTE: Characters typed with `Ctrl` belong to the shortcuts
+    if ctrl_pressed(&keyboard_input) {
+        return;
+    }
 
-    if selection.from == Vec2::NEG_ONE {
-        for piece in pieces.iter() {
-            let center = Vec2::new(piece.x as f32, piece.y as f32) * SIZE;
-            let min = center - HALF_SIZE;
-            let max = center + HALF_SIZE;
+    for c in typed {
+        if c.is_ascii_graphic() || (c == ' ' && !input.text.is_empty()) {
+            input.text.push(c);
+            input.message = None;
+        }
+    }
 
-            let inside_square =
-                point.x > min.x && point.y > min.y && point.x < max.x && point.y < max.y;
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        input.text.pop();
+        input.message = None;
+    }
 
-            if inside_square && turn_manager.same_color(piece.color) {
-                selection.from = pos;
-                break;
+    if keyboard_input.just_pressed(KeyCode::Return) && !input.text.is_empty() {
+        match san::parse(&game.position, &input.text) {
+            Ok(m) => {
+                events.send(MoveEvent(m));
+                input.message = None;
             }
+            Err(error) => input.message = Some(error),
         }
-    } else if pos != selection.from
-        && valid_path(
-            (
-                (selection.from.x / SIZE) as u8,
-                (selection.from.y / SIZE) as u8,
-            ),
-            ((selection.to.x / SIZE) as u8, (selection.to.y / SIZE) as u8),
-            pieces,
-        )
+        input.text.clear();
+    }
+}
+
+fn update_move_input_text(mut query: Query<&mut Text, With<MoveInputText>>, input: Res<MoveInput>) {
+    if !input.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        text.sections[0].value = match &input.message {
+            Some(message) => message.clone(),
+            None if input.text.is_empty() => String::new(),
+            None => format!("> {}", input.text),
+        };
+    }
+}
+
+fn ctrl_pressed(keyboard_input: &Input<KeyCode>) -> bool {
+    keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+}
+
+fn history_input(
+    mut events: EventWriter<HistoryEvent>,
+    mut selection: ResMut<Selection>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    if !ctrl_pressed(&keyboard_input) {
+        return;
+    }
+
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let event = if keyboard_input.just_pressed(KeyCode::Y)
+        || (shift && keyboard_input.just_pressed(KeyCode::Z))
     {
-        selection.to = pos;
+        HistoryEvent::Redo
+    } else if keyboard_input.just_pressed(KeyCode::Z) {
+        HistoryEvent::Undo
+    } else {
+        return;
+    };
+
+    selection.from = Vec2::NEG_ONE;
+    selection.to = Vec2::NEG_ONE;
+    events.send(event);
+}
+
+/// Shows the position as FEN below the board, and prints it for those running from a terminal
+fn export_fen(mut input: ResMut<MoveInput>, keyboard_input: Res<Input<KeyCode>>, game: Res<Game>) {
+    if ctrl_pressed(&keyboard_input) && keyboard_input.just_pressed(KeyCode::F) {
+        let fen = fen::write(&game.position);
+        println!("{}", fen);
+        input.message = Some(fen);
+    }
+}
+
+fn flip_board(mut orientation: ResMut<Orientation>, keyboard_input: Res<Input<KeyCode>>) {
+    if ctrl_pressed(&keyboard_input) && keyboard_input.just_pressed(KeyCode::R) {
+        orientation.flip();
     }
 }
 
-fn valid_path(from: (u8, u8), to: (u8, u8), pieces: Query<&Piece>) -> bool {
-    return true;
-
-    // NOTE: This code is stolen
-    // let x_diff = (self.x - to.0).abs();
-    // let y_diff = (self.y - to.1).abs();
-    // let same_x = self.x == to.0;
-    // let same_y = self.y == to.1;
-    // let path_empty = is_path_empty((self.x, self.y), to, &pieces);
-
-    // match self.my_type {
-    //     PieceType::King => {
-    //         path_empty
-    //             && ((x_diff == 1 && same_y)
-    //                 || (y_diff == 1 && same_x)
-    //                 || (x_diff == 1 && y_diff == 1))
-    //     }
-    //     PieceType::Queen => {
-    //         path_empty && (x_diff == y_diff || ((same_x && !same_y) || (same_y && !same_x)))
-    //     }
-    //     PieceType::Bishop => path_empty && x_diff == y_diff,
-    //     PieceType::Knight => (x_diff == 2 && y_diff == 1) || (x_diff == 1 && y_diff == 2),
-    //     PieceType::Rook => path_empty && ((same_x && !same_y) || (same_y && !same_x)),
-    //     PieceType::Pawn => {
-    //         let color = piece_color(to, &pieces);
-
-    //         if self.color == PieceColor::White {
-    //             if same_x && path_empty {
-    //                 if self.y == 1 {
-    //                     if y_diff == 2 {
-    //                         if color.is_none() {
-    //                             return true;
-    //                         }
-    //                     }
-    //                 } else {
-    //                     if y_diff == 1 {
-    //                         if color.is_none() {
-    //                             return true;
-    //                         }
-    //                     }
-    //                 }
-    //             } else if to.0 - self.x == 1 && y_diff == 1 {
-    //                 if color == Some(PieceColor::Black) {
-    //                     return true;
-    //                 }
-    //             }
-    //         } else {
-    //             if same_x && path_empty {
-    //                 if self.y == 6 {
-    //                     if y_diff == -2 {
-    //                         if color.is_none() {
-    //                             return true;
-    //                         }
-    //                     }
-    //                 } else {
-    //                     if y_diff == -1 {
-    //                         if color.is_none() {
-    //                             return true;
-    //                         }
-    //                     }
-    //                 }
-    //             } else if to.0 - self.x == -1 && y_diff == 1 {
-    //                 if color == Some(PieceColor::White) {
-    //                     return true;
-    //                 }
-    //             }
-    //         }
-
-    //         false
-    //     }
-    // }
+fn save_pgn(
+    keyboard_input: Res<Input<KeyCode>>,
+    game: Res<Game>,
+    file: Res<GameFile>,
+    clocks: Option<Res<Clocks>>,
+) {
+    if !ctrl_pressed(&keyboard_input) || !keyboard_input.just_pressed(KeyCode::S) {
+        return;
+    }
+
+    let mut record = game.record();
+    if let Some(clocks) = clocks {
+        let control = clocks.0.control;
+        record.set_tag("TimeControl", &time_control_tag(control));
+
+        if let Some(loser) = clocks.0.flagged() {
+            let result = match loser {
+                PieceColor::White => "0-1",
+                PieceColor::Black => "1-0",
+            };
+            record.set_tag("Result", result);
+            record.set_tag("Termination", "time forfeit");
+        }
+    }
+
+    match std::fs::write(&file.0, pgn::write(&record)) {
+        Ok(()) => println!("Saved the game to {}", file.0.display()),
+        Err(error) => eprintln!("Could not save the game to {}: {}", file.0.display(), error),
+    }
+}
+
+/// The PGN `TimeControl` value, which has no notation for delays
+fn time_control_tag(control: clock::TimeControl) -> String {
+    let base = control.base.as_secs();
+    match control.bonus {
+        clock::Bonus::Increment(increment) => format!("{}+{}", base, increment.as_secs()),
+        _ => base.to_string(),
+    }
 }
@@ -0,0 +1,10 @@
+//! The chess logic behind the game, usable without any Bevy plugin
+pub mod clock;
+pub mod difficulty;
+pub mod eval;
+pub mod fen;
+pub mod pgn;
+pub mod rules;
+pub mod san;
+pub mod search;
+pub mod uci;
@@ -1,5 +1,9 @@
 use bevy::prelude::*;
+use bevy::render::camera::ScalingMode;
 use bevy::window::*;
+use bevy_project::difficulty::Difficulty;
+use bevy_project::eval::Personality;
+use bevy_project::{clock, eval, fen, pgn, rules, san, search, uci};
 use bevy_vector_shapes::prelude::*;
 
 mod pieces;
@@ -8,9 +12,36 @@ mod board;
 use board::*;
 mod input;
 use input::*;
+mod promotion;
+use promotion::*;
+mod history;
+use history::*;
+mod ui;
+use ui::*;
+mod clock_ui;
+use clock_ui::*;
+mod ai;
+use ai::*;
+mod engine;
+use engine::*;
+mod menu;
+use menu::*;
 
 fn main() {
-    App::new()
+    let (game, file) = start_game();
+
+    let mut app = App::new();
+    if let Some(control) = time_control() {
+        app.insert_resource(Clocks(clock::Clock::new(control, game.position.side)));
+    }
+    app.insert_resource(opponent(&game));
+    if let Some(engine) = engine_player() {
+        app.insert_resource(engine);
+    }
+
+    app.insert_resource(TurnManager::new(game.position.side))
+        .insert_resource(game)
+        .insert_resource(file)
         .insert_resource(AmbientLight {
             color: Color::default(),
             brightness: 0.65,
@@ -20,8 +51,12 @@ fn main() {
                 .set(ImagePlugin::default_nearest())
                 .set(WindowPlugin {
                     primary_window: Some(Window {
-                        mode: WindowMode::BorderlessFullscreen,
-                        resizable: false,
+                        mode: if std::env::args().any(|arg| arg == "--windowed") {
+                            WindowMode::Windowed
+                        } else {
+                            WindowMode::BorderlessFullscreen
+                        },
+                        resizable: true,
                         ..default()
                     }),
                     ..default()
@@ -30,7 +65,13 @@ fn main() {
         .add_plugins(BoardPlugin)
         .add_plugins(PiecesPlugin)
         .add_plugins(InputPlugin)
-        // .add_plugin(UIPlugin)
+        .add_plugins(PromotionPlugin)
+        .add_plugins(HistoryPlugin)
+        .add_plugins(UIPlugin)
+        .add_plugins(ClockUiPlugin)
+        .add_plugins(AiPlugin)
+        .add_plugins(EnginePlugin)
+        .add_plugins(MenuPlugin)
         .add_plugins(Shape2dPlugin::default())
         .add_systems(PreStartup, setup)
         .run();
@@ -39,8 +80,132 @@ fn main() {
 fn setup(mut commands: Commands) {
     let val = SIZE * (MAX as f32 * 0.5);
 
-    commands.spawn(Camera2dBundle {
+    let mut camera = Camera2dBundle {
         transform: Transform::from_translation(Vec3::new(val, val, 0.)),
         ..Default::default()
+    };
+    // NOTE: Shrinks or grows the view to the window, keeping the whole `VIEW` in sight
+    camera.projection.scaling_mode = ScalingMode::AutoMin {
+        min_width: VIEW.x,
+        min_height: VIEW.y,
+    };
+    commands.spawn(camera);
+}
+
+fn argument(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
+}
+
+/// The duration given in seconds with `name`, warning about negative or unreadable ones
+fn seconds(name: &str) -> Option<std::time::Duration> {
+    let text = argument(name)?;
+
+    text.parse()
+        .ok()
+        .and_then(|seconds| std::time::Duration::try_from_secs_f32(seconds).ok())
+        .or_else(|| {
+            eprintln!("Ignoring invalid {} '{}'", name, text);
+            None
+        })
+}
+
+/// The time control given with `--clock`, games without one are untimed
+fn time_control() -> Option<clock::TimeControl> {
+    let text = argument("--clock")?;
+
+    clock::parse(&text)
+        .map_err(|error| eprintln!("Ignoring {}", error))
+        .ok()
+}
+
+/// The computer player preselected in the menu, from `--ai <white|black>`, `--ai-level`,
+/// `--ai-style`, `--ai-depth` and `--ai-time`, or else from the tags of a loaded game
+fn opponent(game: &Game) -> Opponent {
+    let mut opponent = Opponent::default();
+
+    for (name, color) in [("White", PieceColor::White), ("Black", PieceColor::Black)] {
+        if game.tag(name) == Some("Computer") {
+            opponent.computer = Some(color);
+        }
+    }
+    let level = argument("--ai-level").or(game.tag("Difficulty").map(String::from));
+    let style = argument("--ai-style").or(game.tag("Personality").map(String::from));
+
+    match argument("--ai")
+        .map(|color| color.to_lowercase())
+        .as_deref()
+    {
+        Some("white") => opponent.computer = Some(PieceColor::White),
+        Some("black") => opponent.computer = Some(PieceColor::Black),
+        Some(other) => eprintln!("Ignoring invalid AI color '{}'", other),
+        None => {}
+    }
+    match level.map(|level| level.parse()) {
+        Some(Ok(difficulty)) => opponent.difficulty = difficulty,
+        Some(Err(error)) => eprintln!("Ignoring {}", error),
+        None => {}
+    }
+    match style.map(|style| style.parse()) {
+        Some(Ok(personality)) => opponent.personality = personality,
+        Some(Err(error)) => eprintln!("Ignoring {}", error),
+        None => {}
+    }
+
+    opponent.depth = argument("--ai-depth").and_then(|depth| depth.parse().ok());
+    opponent.time = seconds("--ai-time");
+
+    opponent
+}
+
+/// The UCI engine at `--engine <PATH>`, playing `--engine-color` (Black by default)
+/// for `--engine-time` seconds a move in untimed games
+fn engine_player() -> Option<EnginePlayer> {
+    let path = argument("--engine")?;
+
+    let color = match argument("--engine-color")
+        .map(|color| color.to_lowercase())
+        .as_deref()
+    {
+        Some("white") => PieceColor::White,
+        Some("black") | None => PieceColor::Black,
+        Some(other) => {
+            eprintln!("Ignoring invalid engine color '{}'", other);
+            PieceColor::Black
+        }
+    };
+    let move_time = seconds("--engine-time").unwrap_or(std::time::Duration::from_secs(1));
+
+    let mut engine = uci::Engine::start(&path)
+        .and_then(|mut engine| engine.new_game().map(|()| engine))
+        .map_err(|error| eprintln!("Could not start the engine '{}': {}", path, error))
+        .ok()?;
+    if let Some(name) = engine.name.take() {
+        println!("Playing against {}", name);
+    }
+
+    Some(EnginePlayer::new(color, engine, move_time))
+}
+
+/// The game loaded with `--pgn <FILE>`, or a new one from `--fen "<FEN>"` or the standard start
+fn start_game() -> (Game, GameFile) {
+    if let Some(path) = argument("--pgn") {
+        let game = std::fs::read_to_string(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|text| pgn::parse(&text));
+
+        match game {
+            Ok(record) => return (Game::from_record(&record), GameFile(path.into())),
+            Err(error) => eprintln!("Ignoring invalid PGN '{}': {}", path, error),
+        }
+    }
+
+    let Some(fen) = argument("--fen") else {
+        return (Game::default(), GameFile::default());
+    };
+
+    let position = fen::parse(&fen).unwrap_or_else(|error| {
+        eprintln!("Ignoring invalid FEN '{}': {}", fen, error);
+        rules::Position::start()
     });
+    (Game::new(position), GameFile::default())
 }
@@ -0,0 +1,232 @@
+use super::*;
+use std::time::Duration;
+
+/// Who the human plays against, chosen in the pre-game menu
+#[derive(Resource, Clone, Default)]
+pub struct Opponent {
+    /// The color played by the computer, `None` for two humans at the board
+    pub computer: Option<PieceColor>,
+    pub difficulty: Difficulty,
+    pub personality: Personality,
+    /// `--ai-depth` and `--ai-time`, overriding the limits of the difficulty
+    pub depth: Option<u32>,
+    pub time: Option<Duration>,
+}
+
+impl Opponent {
+    pub fn ai_player(&self) -> Option<AiPlayer> {
+        let mut ai = AiPlayer::new(self.computer?, self.difficulty, self.personality);
+        if let Some(depth) = self.depth {
+            ai.limits.depth = depth;
+        }
+        if self.time.is_some() {
+            ai.limits.time = self.time;
+        }
+        Some(ai)
+    }
+}
+
+#[derive(Component)]
+struct Menu;
+
+#[derive(Component, Clone, Copy, PartialEq)]
+enum MenuButton {
+    Computer(Option<PieceColor>),
+    Difficulty(Difficulty),
+    Personality(Personality),
+    Start,
+}
+
+pub struct MenuPlugin;
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Opponent>()
+            .add_systems(OnEnter(GameState::Menu), spawn_menu)
+            .add_systems(
+                Update,
+                (
+                    press_menu_button,
+                    highlight_choices.run_if(resource_changed::<Opponent>()),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Menu)),
+            )
+            .add_systems(OnExit(GameState::Menu), (despawn_menu, apply_opponent));
+    }
+}
+
+fn spawn_menu(
+    mut commands: Commands,
+    mut opponent: ResMut<Opponent>,
+    engine: Option<Res<EnginePlayer>>,
+) {
+    let root = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(HALF_SIZE * 0.5),
+            ..default()
+        },
+        background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+        z_index: ZIndex::Global(10),
+        ..default()
+    };
+
+    // NOTE: The side of the `--engine` player is not offered, each side has one computer at most
+    let engine_color = engine.map(|engine| engine.color);
+    if opponent.computer.is_some() && opponent.computer == engine_color {
+        opponent.computer = None;
+    }
+    let computers: Vec<_> = [
+        ("Nobody", None),
+        ("White", Some(PieceColor::White)),
+        ("Black", Some(PieceColor::Black)),
+    ]
+    .into_iter()
+    .filter(|(_, color)| color.is_none() || *color != engine_color)
+    .map(|(label, color)| (label.to_string(), MenuButton::Computer(color)))
+    .collect();
+    let difficulties = Difficulty::ALL.map(|difficulty| {
+        let label = format!("{:?} ({})", difficulty, difficulty.elo());
+        (label, MenuButton::Difficulty(difficulty))
+    });
+    let personalities = Personality::ALL.map(|personality| {
+        (
+            format!("{:?}", personality),
+            MenuButton::Personality(personality),
+        )
+    });
+
+    commands.spawn((root, Menu)).with_children(|parent| {
+        parent.spawn(menu_text("New game", SIZE * 0.6));
+
+        parent.spawn(menu_text("The computer plays", HALF_SIZE * 0.6));
+        spawn_row(parent, computers);
+        parent.spawn(menu_text("Difficulty", HALF_SIZE * 0.6));
+        spawn_row(parent, difficulties);
+        parent.spawn(menu_text("Personality", HALF_SIZE * 0.6));
+        spawn_row(parent, personalities);
+
+        spawn_row(parent, [("Start".to_string(), MenuButton::Start)]);
+    });
+}
+
+fn spawn_row(parent: &mut ChildBuilder, buttons: impl IntoIterator<Item = (String, MenuButton)>) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                column_gap: Val::Px(HALF_SIZE * 0.25),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for (label, button) in buttons {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(HALF_SIZE * 0.25)),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        button,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(menu_text(&label, HALF_SIZE * 0.5));
+                    });
+            }
+        });
+}
+
+fn menu_text(value: &str, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size,
+            ..default()
+        },
+    )
+}
+
+fn press_menu_button(
+    mut opponent: ResMut<Opponent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *button {
+            MenuButton::Computer(color) => opponent.computer = color,
+            MenuButton::Difficulty(difficulty) => opponent.difficulty = difficulty,
+            MenuButton::Personality(personality) => opponent.personality = personality,
+            MenuButton::Start => next_state.set(GameState::Playing),
+        }
+    }
+}
+
+fn highlight_choices(
+    mut buttons: Query<(&MenuButton, &mut BackgroundColor)>,
+    opponent: Res<Opponent>,
+) {
+    for (button, mut background) in buttons.iter_mut() {
+        let chosen = match *button {
+            MenuButton::Computer(color) => color == opponent.computer,
+            MenuButton::Difficulty(difficulty) => difficulty == opponent.difficulty,
+            MenuButton::Personality(personality) => personality == opponent.personality,
+            MenuButton::Start => false,
+        };
+
+        *background = if chosen {
+            Color::rgb(0.45, 0.3, 0.2).into()
+        } else {
+            Color::rgb(0.15, 0.15, 0.15).into()
+        };
+    }
+}
+
+fn despawn_menu(mut commands: Commands, query: Query<Entity, With<Menu>>) {
+    for id in query.iter() {
+        commands.entity(id).despawn_recursive();
+    }
+}
+
+/// Hands the computer its pieces, records it with the game and turns the board to the human
+fn apply_opponent(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    mut orientation: ResMut<Orientation>,
+    opponent: Res<Opponent>,
+    engine: Option<Res<EnginePlayer>>,
+) {
+    // NOTE: The computer of a loaded game may have been changed in the menu
+    game.tags.retain(|(name, value)| {
+        value != "Computer" && name != "Difficulty" && name != "Personality"
+    });
+
+    match opponent.ai_player() {
+        Some(ai) => {
+            ai.record(&mut game);
+            commands.insert_resource(ai);
+        }
+        None => commands.remove_resource::<AiPlayer>(),
+    }
+
+    // NOTE: A human playing Black against the computer sees the board from Black's side
+    let computer = opponent.computer.or(engine.map(|engine| engine.color));
+    orientation.0 = match computer {
+        Some(PieceColor::White) => PieceColor::Black,
+        _ => PieceColor::White,
+    };
+
+    // NOTE: A loaded game may already be over, which is only checked once the game changes
+    game.set_changed();
+}
@@ -0,0 +1,270 @@
+//! Portable Game Notation records of whole games
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{fen, rules::*, san};
+
+/// The tags every PGN game carries, always exported first and in this order
+pub const SEVEN_TAG_ROSTER: [&str; 7] =
+    ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+const LINE_WIDTH: usize = 80;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Record {
+    pub tags: Vec<(String, String)>,
+    pub start: Position,
+    pub moves: Vec<Move>,
+}
+
+impl Record {
+    /// A record of `moves` played from `start`, with the Seven Tag Roster filled in
+    pub fn new(start: Position, moves: Vec<Move>) -> Self {
+        let mut record = Self {
+            tags: Vec::new(),
+            start,
+            moves,
+        };
+
+        for name in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Date" => today(),
+                "Result" => result(&record.position()).to_string(),
+                _ => "?".to_string(),
+            };
+            record.set_tag(name, &value);
+        }
+
+        record
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// The position reached after playing every move
+    pub fn position(&self) -> Position {
+        self.moves
+            .iter()
+            .fold(self.start.clone(), |position, m| apply(&position, *m))
+    }
+}
+
+/// The PGN game termination marker for `position`
+pub fn result(position: &Position) -> &'static str {
+    match outcome(position) {
+        Some(Outcome::Checkmate(PieceColor::White)) => "1-0",
+        Some(Outcome::Checkmate(PieceColor::Black)) => "0-1",
+        Some(Outcome::Stalemate) => "1/2-1/2",
+        None => "*",
+    }
+}
+
+pub fn write(record: &Record) -> String {
+    let mut text = String::new();
+
+    let mut tags: Vec<(&str, &str)> = SEVEN_TAG_ROSTER
+        .iter()
+        .map(|name| (*name, record.tag(name).unwrap_or("?")))
+        .collect();
+    let start_fen = fen::write(&record.start);
+    if record.start != Position::start() {
+        tags.push(("SetUp", "1"));
+        tags.push(("FEN", &start_fen));
+    }
+    for (name, value) in &record.tags {
+        let known = SEVEN_TAG_ROSTER.contains(&name.as_str()) || name == "SetUp" || name == "FEN";
+        if !known {
+            tags.push((name, value));
+        }
+    }
+
+    for (name, value) in tags {
+        let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+        text.push_str(&format!("[{} \"{}\"]\n", name, value));
+    }
+    text.push('\n');
+
+    let mut tokens = Vec::new();
+    let mut position = record.start.clone();
+    for (i, m) in record.moves.iter().enumerate() {
+        if position.side == PieceColor::White {
+            tokens.push(format!("{}.", position.fullmove_number));
+        } else if i == 0 {
+            tokens.push(format!("{}...", position.fullmove_number));
+        }
+
+        tokens.push(san::write(&position, *m));
+        position = apply(&position, *m);
+    }
+    tokens.push(record.tag("Result").unwrap_or("*").to_string());
+
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() >= LINE_WIDTH {
+            text.push_str(&line);
+            text.push('\n');
+            line.clear();
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    text.push_str(&line);
+    text.push('\n');
+
+    text
+}
+
+/// Reads the first game in `text`, skipping comments, variations and annotation glyphs
+pub fn parse(text: &str) -> Result<Record, String> {
+    let mut tags = Vec::new();
+    let mut movetext = String::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            // NOTE: Tags after the movetext belong to the next game
+            if !movetext.trim().is_empty() {
+                break;
+            }
+            tags.push(parse_tag(line)?);
+        } else if !line.starts_with('%') {
+            movetext.push_str(line);
+            movetext.push('\n');
+        }
+    }
+
+    let start = match tags.iter().find(|(name, _)| name == "FEN") {
+        Some((_, fen)) => fen::parse(fen)?,
+        None => Position::start(),
+    };
+
+    let mut position = start.clone();
+    let mut moves = Vec::new();
+    for token in movetext_tokens(&movetext) {
+        if matches!(token.as_str(), "1-0" | "0-1" | "1/2-1/2" | "*") {
+            break;
+        }
+        // NOTE: The en passant mark written after a capture adds nothing to the move before it
+        if token == "e.p." {
+            continue;
+        }
+
+        // NOTE: Only a move number is stripped, castling written `0-0` starts with a digit as well
+        let san = match token.rsplit_once('.') {
+            Some((number, san)) if number.chars().all(|c| c.is_ascii_digit() || c == '.') => san,
+            _ => token.as_str(),
+        };
+        if san.is_empty() {
+            continue;
+        }
+
+        let m = san::parse(&position, san)?;
+        position = apply(&position, m);
+        moves.push(m);
+    }
+
+    Ok(Record { tags, start, moves })
+}
+
+fn parse_tag(line: &str) -> Result<(String, String), String> {
+    let invalid = || format!("invalid tag '{}'", line);
+
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|line| line.strip_suffix(']'))
+        .ok_or_else(invalid)?;
+    let (name, value) = inner.split_once(char::is_whitespace).ok_or_else(invalid)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+
+    Ok((
+        name.to_string(),
+        value.replace("\\\"", "\"").replace("\\\\", "\\"),
+    ))
+}
+
+fn movetext_tokens(movetext: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut chars = movetext.chars();
+    let mut variation_depth = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                chars.by_ref().find(|c| *c == '}');
+            }
+            ';' => {
+                chars.by_ref().find(|c| *c == '\n');
+            }
+            '(' => variation_depth += 1,
+            ')' => variation_depth -= 1,
+            _ if variation_depth > 0 => {}
+            _ if c.is_whitespace() => {
+                if !token.is_empty() && !token.starts_with('$') {
+                    tokens.push(token.clone());
+                }
+                token.clear();
+                continue;
+            }
+            _ => {
+                token.push(c);
+                continue;
+            }
+        }
+
+        // NOTE: Comments and variations also end the token before them
+        if !token.is_empty() && !token.starts_with('$') {
+            tokens.push(token.clone());
+        }
+        token.clear();
+    }
+
+    if !token.is_empty() && !token.starts_with('$') {
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+/// The current UTC date as `YYYY.MM.DD`
+fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+
+    // NOTE: Converts days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
@@ -1,23 +1,10 @@
 use super::*;
 
-const ORDER_LAYER: f32 = 5.0;
-
-#[derive(Clone, Copy, PartialEq, Debug, Default)]
-pub enum PieceColor {
-    #[default]
-    White,
-    Black,
-}
+pub use bevy_project::rules::{PieceColor, PieceType};
 
-#[derive(Clone, Copy, PartialEq, Debug)]
-pub enum PieceType {
-    King,
-    Queen,
-    Bishop,
-    Knight,
-    Rook,
-    Pawn,
-}
+const ORDER_LAYER: f32 = 5.0;
+/// Above the other pieces, for the one being dragged
+pub const DRAGGED_LAYER: f32 = ORDER_LAYER + 1.0;
 
 #[derive(Component, Clone, Copy)]
 pub struct Piece {
@@ -28,207 +15,318 @@ pub struct Piece {
     pub y: u8,
 }
 
-pub struct PiecesPlugin;
-impl Plugin for PiecesPlugin {
-    fn build(&self, app: &mut App) {
-        // app.add_state::<Game>()
-        app.add_systems(Startup, spawn_pieces)
-            .add_systems(PostStartup, load_sprites)
-            .add_systems(Update, move_pieces);
-    }
+/// The game being played, the `Piece` entities are spawned from its position
+#[derive(Resource, Default)]
+pub struct Game {
+    pub start: rules::Position,
+    pub moves: Vec<rules::Move>,
+    pub position: rules::Position,
+    /// Taken back moves, the next one to redo last
+    pub undone: Vec<rules::Move>,
+    /// The number of moves played in the position on the board, `None` for the latest one
+    pub viewed: Option<usize>,
+    /// The PGN tags saved with the game, like the players or the computer's difficulty
+    pub tags: Vec<(String, String)>,
 }
 
-fn spawn_pieces(mut commands: Commands) {
-    let chess_pieces: [(PieceType, Vec<(u8, u8)>); 6] = [
-        (PieceType::King, vec![(4, 0)]),
-        (PieceType::Queen, vec![(3, 0)]),
-        (PieceType::Rook, vec![(0, 0), (7, 0)]),
-        (PieceType::Bishop, vec![(1, 0), (6, 0)]),
-        (PieceType::Knight, vec![(2, 0), (5, 0)]),
-        (PieceType::Pawn, (0..MAX).map(|x| (x, 1)).collect()),
-    ];
-
-    for (my_type, pos_arr) in chess_pieces.into_iter() {
-        for i in 0..pos_arr.len() {
-            commands.spawn(Piece {
-                my_type: my_type,
-                color: PieceColor::White,
-                x: pos_arr[i].0,
-                y: pos_arr[i].1,
-            });
-            commands.spawn(Piece {
-                my_type: my_type,
-                color: PieceColor::Black,
-                x: pos_arr[i].0,
-                y: MAX - 1 - pos_arr[i].1,
-            });
+impl Game {
+    pub fn new(start: rules::Position) -> Self {
+        Self {
+            position: start.clone(),
+            start,
+            moves: Vec::new(),
+            undone: Vec::new(),
+            viewed: None,
+            tags: Vec::new(),
         }
     }
+
+    pub fn from_record(record: &pgn::Record) -> Self {
+        Self {
+            start: record.start.clone(),
+            moves: record.moves.clone(),
+            position: record.position(),
+            undone: Vec::new(),
+            viewed: None,
+            tags: record.tags.clone(),
+        }
+    }
+
+    /// The game as a PGN record, its result always computed from the moves
+    pub fn record(&self) -> pgn::Record {
+        let mut record = pgn::Record::new(self.start.clone(), self.moves.clone());
+        for (name, value) in &self.tags {
+            if name != "Result" {
+                record.set_tag(name, value);
+            }
+        }
+        record
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn play(&mut self, m: rules::Move) {
+        // NOTE: Replaying the undone move keeps the rest of the line redoable
+        if self.undone.last() == Some(&m) {
+            self.undone.pop();
+        } else {
+            self.undone.clear();
+        }
+
+        self.position = rules::apply(&self.position, m);
+        self.moves.push(m);
+        self.viewed = None;
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some(m) = self.moves.pop() else {
+            return false;
+        };
+
+        self.undone.push(m);
+        self.position = self.position_at(self.moves.len());
+        self.viewed = None;
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some(m) = self.undone.pop() else {
+            return false;
+        };
+
+        self.position = rules::apply(&self.position, m);
+        self.moves.push(m);
+        self.viewed = None;
+        true
+    }
+
+    /// The position after the first `ply` moves
+    pub fn position_at(&self, ply: usize) -> rules::Position {
+        self.moves[..ply]
+            .iter()
+            .fold(self.start.clone(), |position, m| {
+                rules::apply(&position, *m)
+            })
+    }
+
+    /// The position shown on the board, only the latest one can be played on
+    pub fn shown(&self) -> rules::Position {
+        match self.viewed {
+            Some(ply) => self.position_at(ply),
+            None => self.position.clone(),
+        }
+    }
+
+    pub fn at_latest(&self) -> bool {
+        self.viewed.is_none()
+    }
+}
+
+/// Takes back the last move or plays the last taken back one again
+#[derive(Event, Clone, Copy, PartialEq, Debug)]
+pub enum HistoryEvent {
+    Undo,
+    Redo,
+}
+
+/// A move chosen by a player, played once it is checked to be legal
+#[derive(Event, Clone, Copy)]
+pub struct MoveEvent(pub rules::Move);
+
+#[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum GameState {
+    /// The pre-game menu choosing the opponent
+    #[default]
+    Menu,
+    Playing,
+    Promotion,
+    GameOver,
 }
 
-fn load_sprites(
+pub struct PiecesPlugin;
+impl Plugin for PiecesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<GameState>()
+            .init_resource::<Game>()
+            .add_event::<MoveEvent>()
+            .add_event::<HistoryEvent>()
+            .add_systems(
+                Update,
+                (
+                    (move_pieces.run_if(viewing_latest), play_moves)
+                        .chain()
+                        .run_if(in_state(GameState::Playing)),
+                    step_history,
+                    sync_pieces.run_if(
+                        resource_changed::<Game>().or_else(resource_changed::<Orientation>()),
+                    ),
+                    check_game_over
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(resource_changed::<Game>()),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Despawns the `Piece` entities missing from the current position and spawns the new ones,
+/// placed for the current `Orientation`
+fn sync_pieces(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    query: Query<(Entity, &Piece)>,
+    game: Res<Game>,
+    orientation: Res<Orientation>,
+    mut query: Query<(Entity, &Piece, &mut Transform)>,
 ) {
-    for (id, piece) in query.iter() {
-        commands.entity(id).insert(SpriteBundle {
-            texture: {
-                let image_path = format!(
-                    "ARABIAN CHESS/sprites/pieces/{:?}_{:?}.png",
-                    piece.color, piece.my_type
-                )
-                .to_lowercase();
-                asset_server.load(image_path)
+    let shown = game.shown();
+    let mut missing: Vec<_> = shown.pieces().collect();
+
+    for (id, piece, mut transform) in query.iter_mut() {
+        let found = missing.iter().position(|&(pos, color, my_type)| {
+            pos == (piece.x, piece.y) && color == piece.color && my_type == piece.my_type
+        });
+
+        match found {
+            Some(i) => {
+                missing.swap_remove(i);
+                // NOTE: Puts back a piece dropped on a square it did not move to
+                transform.translation = piece_translation(&orientation, (piece.x, piece.y));
+            }
+            None => commands.entity(id).despawn(),
+        }
+    }
+
+    for ((x, y), color, my_type) in missing {
+        commands.spawn((
+            Piece {
+                my_type,
+                color,
+                x,
+                y,
             },
-            transform: {
-                let pos = Vec3::new(piece.x as f32 * SIZE, piece.y as f32 * SIZE, ORDER_LAYER);
-                let scale = Vec3::new(SIZE / 16., SIZE / 16., 1.);
+            SpriteBundle {
+                texture: asset_server.load(sprite_path(color, my_type)),
+                transform: {
+                    let scale = Vec3::new(SIZE / 16., SIZE / 16., 1.);
 
-                Transform::from_translation(pos).with_scale(scale)
+                    Transform::from_translation(piece_translation(&orientation, (x, y)))
+                        .with_scale(scale)
+                },
+                ..default()
             },
-            ..default()
-        });
+        ));
     }
 }
 
+pub fn sprite_path(color: PieceColor, my_type: PieceType) -> String {
+    format!("ARABIAN CHESS/sprites/pieces/{:?}_{:?}.png", color, my_type).to_lowercase()
+}
+
+/// Where the sprite of a piece standing on `square` is drawn
+pub fn piece_translation(orientation: &Orientation, square: rules::Square) -> Vec3 {
+    orientation.translation(square, ORDER_LAYER)
+}
+
 fn move_pieces(
     mut commands: Commands,
     mut selection: ResMut<Selection>,
-    mut query: Query<(Entity, &mut Piece, &mut Transform)>,
-    mut manager: ResMut<TurnManager>,
+    mut events: EventWriter<MoveEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    game: Res<Game>,
 ) {
     if selection.to == Vec2::NEG_ONE {
         return;
     }
 
-    // let mut piece_vector = Vec::new();
-    let mut attacker: Option<(Mut<'_, Piece>, Mut<'_, Transform>)> = None;
-    let mut defender: Option<(Mut<'_, Piece>, Mut<'_, Transform>, Entity)> = None;
+    let from = (selection.from.x as u8, selection.from.y as u8);
+    let to = (selection.to.x as u8, selection.to.y as u8);
+    selection.from = Vec2::NEG_ONE;
+    selection.to = Vec2::NEG_ONE;
+
+    let moves: Vec<rules::Move> = rules::legal_moves(&game.position)
+        .into_iter()
+        .filter(|m| m.from == from && m.to == to)
+        .collect();
 
-    for (id, piece, transform) in query.iter_mut() {
-        let pos = Vec2::new(piece.x as f32, piece.y as f32);
-        if pos == selection.from {
-            attacker = Some((piece, transform));
-        } else if pos == selection.to {
-            defender = Some((piece, transform, id));
+    match moves.as_slice() {
+        [] => {}
+        [m] => events.send(MoveEvent(*m)),
+        // NOTE: Moves sharing both squares only differ by the promoted piece
+        _ => {
+            commands.insert_resource(Promotion(rules::Move::new(from, to)));
+            next_state.set(GameState::Promotion);
         }
-
-        // piece_vector.push(piece);
     }
+}
 
-    let Some(attack) = attacker else {
-        return;
-    };
-
-    // TODO: fill arguments
-    // if attack.0.my_type != PieceType::Knight
-    //     && !path_empty(
-    //         (
-    //             (selection.old.x / SIZE) as u8,
-    //             (selection.old.y / SIZE) as u8,
-    //         ),
-    //         (
-    //             (selection.new.x / SIZE) as u8,
-    //             (selection.new.y / SIZE) as u8,
-    //         ),
-    //         piece_vector,
-    //     )
-    // {
-    //     return;
-    // }
-
-    if let Some(defend) = defender {
-        if defend.0.color == attack.0.color {
-            let king_rook = (attack.0.my_type == PieceType::King
-                && defend.0.my_type == PieceType::Rook)
-                || (attack.0.my_type == PieceType::Rook && defend.0.my_type == PieceType::King);
-            if king_rook {
-                // FIX: Check the following conditions rules
-                // NOTE: 1. Neither the king nor the rook involved in the switch must have moved previously.
-                // NOTE: 2. There should be no pieces between the king and the rook.
-                // NOTE: 3. The king should not be in check.
-                // NOTE: 4. The squares that the king crosses and ends up on should not be under attack.
-
-                move_piece(defend.0, defend.1, selection.from);
-                move_piece(attack.0, attack.1, selection.to);
-                selection.from = Vec2::NEG_ONE;
-                selection.to = Vec2::NEG_ONE;
-                manager.next_turn();
-                return;
-            }
-
-            selection.to = Vec2::NEG_ONE;
-            return;
+fn play_moves(
+    mut events: EventReader<MoveEvent>,
+    mut game: ResMut<Game>,
+    mut manager: ResMut<TurnManager>,
+) {
+    for MoveEvent(m) in events.read() {
+        if rules::legal_moves(&game.position).contains(m) {
+            game.play(*m);
+            manager.next_turn();
         }
-
-        commands.entity(defend.2).despawn();
     }
+}
 
-    move_piece(attack.0, attack.1, selection.to);
-    selection.from = Vec2::NEG_ONE;
-    selection.to = Vec2::NEG_ONE;
-    manager.next_turn();
+/// Run condition keeping the board read-only while an earlier position is viewed
+pub fn viewing_latest(game: Res<Game>) -> bool {
+    game.at_latest()
+}
 
-    fn move_piece(mut piece: Mut<'_, Piece>, mut transform: Mut<'_, Transform>, pos: Vec2) {
-        piece.x = pos.x as u8;
-        piece.y = pos.y as u8;
+fn step_history(
+    mut commands: Commands,
+    mut events: EventReader<HistoryEvent>,
+    mut game: ResMut<Game>,
+    mut manager: ResMut<TurnManager>,
+    mut next_state: ResMut<NextState<GameState>>,
+    state: Res<State<GameState>>,
+    computers: Computers,
+) {
+    for event in events.read() {
+        // NOTE: Undoing while picking a promotion only cancels the picker, the pawn has not moved yet
+        if *state.get() == GameState::Promotion {
+            if *event == HistoryEvent::Undo {
+                commands.remove_resource::<Promotion>();
+                // NOTE: Puts a pawn dropped on the last rank back on its square
+                game.set_changed();
+                next_state.set(GameState::Playing);
+            }
+            continue;
+        }
+
+        let step = |game: &mut Game| match event {
+            HistoryEvent::Undo => game.undo(),
+            HistoryEvent::Redo => game.redo(),
+        };
 
-        transform.translation = Vec3::new(pos.x * SIZE, pos.y * SIZE, ORDER_LAYER);
+        // NOTE: Against the computer a step goes back to, or over, its reply as well
+        let stepped = step(&mut game);
+        while stepped && computers.play(game.position.side) && step(&mut game) {}
+        if stepped {
+            *manager = TurnManager::new(game.position.side);
+            next_state.set(GameState::Playing);
+        }
     }
 }
 
-fn path_empty(from: (u8, u8), to: (u8, u8), pieces: Vec<&Piece>) -> bool {
-    // TODO: Check that there are no pieces in between the two points
-    return true;
-
-    // NOTE: Stolen code
-    //     if begin.0 == end.0 {
-    //         for piece in pieces {
-    //             if piece.x == begin.0
-    //                 && ((piece.y > begin.1 && piece.y < end.1)
-    //                     || (piece.y > end.1 && piece.y < begin.1))
-    //             {
-    //                 return false;
-    //             }
-    //         }
-    //     }
-
-    //     if begin.1 == end.1 {
-    //         for piece in pieces {
-    //             if piece.y == begin.1
-    //                 && ((piece.x > begin.0 && piece.x < end.0)
-    //                     || (piece.x > end.0 && piece.x < begin.0))
-    //             {
-    //                 return false;
-    //             }
-    //         }
-    //     }
-
-    //     let x_diff = (begin.0 - end.0).abs();
-    //     let y_diff = (begin.1 - end.1).abs();
-    //     if x_diff == y_diff {
-    //         for i in 1..x_diff {
-    //             let pos = if begin.0 < end.0 && begin.1 < end.1 {
-    //                 // left bottom - right top
-    //                 (begin.0 + i, begin.1 + i)
-    //             } else if begin.0 < end.0 && begin.1 > end.1 {
-    //                 // left top - right bottom
-    //                 (begin.0 + i, begin.1 - i)
-    //             } else if begin.0 > end.0 && begin.1 < end.1 {
-    //                 // right bottom - left top
-    //                 (begin.0 - i, begin.1 + i)
-    //             } else {
-    //                 // begin.0 > end.0 && begin.1 > end.1
-    //                 // right top - left bottom
-    //                 (begin.0 - i, begin.1 - i)
-    //             };
-
-    //             if piece_color(pos, pieces).is_some() {
-    //                 return false;
-    //             }
-    //         }
-    //     }
-
-    //     true
+fn check_game_over(mut next_state: ResMut<NextState<GameState>>, game: Res<Game>) {
+    if rules::outcome(&game.position).is_some() {
+        next_state.set(GameState::GameOver);
+    }
 }
@@ -0,0 +1,114 @@
+use super::*;
+
+const CHOICES: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+];
+
+/// The pawn move waiting for the piece it promotes to
+#[derive(Resource)]
+pub struct Promotion(pub rules::Move);
+
+#[derive(Component)]
+struct PromotionPicker;
+
+#[derive(Component)]
+struct PromotionChoice(PieceType);
+
+pub struct PromotionPlugin;
+impl Plugin for PromotionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Promotion), spawn_picker)
+            .add_systems(
+                Update,
+                pick_promotion.run_if(in_state(GameState::Promotion)),
+            )
+            .add_systems(OnExit(GameState::Promotion), despawn_picker);
+    }
+}
+
+fn spawn_picker(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    turn_manager: Res<TurnManager>,
+) {
+    let root = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        ..default()
+    };
+    let panel = NodeBundle {
+        style: Style {
+            column_gap: Val::Px(HALF_SIZE * 0.5),
+            padding: UiRect::all(Val::Px(HALF_SIZE * 0.5)),
+            ..default()
+        },
+        background_color: Color::rgba(0.0, 0.0, 0.0, 0.75).into(),
+        ..default()
+    };
+
+    commands
+        .spawn((root, PromotionPicker))
+        .with_children(|parent| {
+            parent.spawn(panel).with_children(|parent| {
+                for my_type in CHOICES {
+                    parent.spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(SIZE),
+                                height: Val::Px(SIZE),
+                                ..default()
+                            },
+                            image: UiImage::new(
+                                asset_server.load(sprite_path(turn_manager.color(), my_type)),
+                            ),
+                            ..default()
+                        },
+                        PromotionChoice(my_type),
+                    ));
+                }
+            });
+        });
+}
+
+fn pick_promotion(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut events: EventWriter<MoveEvent>,
+    promotion: Res<Promotion>,
+    mut choices: Query<
+        (&Interaction, &PromotionChoice, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, choice, mut background) in choices.iter_mut() {
+        match interaction {
+            Interaction::Hovered => *background = Color::GRAY.into(),
+            Interaction::None => *background = Color::WHITE.into(),
+            Interaction::Pressed => {
+                events.send(MoveEvent(rules::Move {
+                    promotion: Some(choice.0),
+                    ..promotion.0
+                }));
+
+                commands.remove_resource::<Promotion>();
+                next_state.set(GameState::Playing);
+                return;
+            }
+        }
+    }
+}
+
+fn despawn_picker(mut commands: Commands, query: Query<Entity, With<PromotionPicker>>) {
+    for id in query.iter() {
+        commands.entity(id).despawn_recursive();
+    }
+}
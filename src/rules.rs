@@ -0,0 +1,518 @@
+//! Chess rules over a plain `Position`, free of any Bevy types so they can be
+//! tested and reused without a running `App`.
+
+pub const MAX: u8 = 8;
+
+/// Board coordinates as (file, rank), both in `0..MAX`
+pub type Square = (u8, u8);
+
+const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_STEPS: [(i8, i8); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+const ROOK_STEPS: [(i8, i8); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const BISHOP_STEPS: [(i8, i8); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+const PROMOTIONS: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum PieceColor {
+    #[default]
+    White,
+    Black,
+}
+
+impl PieceColor {
+    pub fn opposite(self) -> Self {
+        match self {
+            PieceColor::White => PieceColor::Black,
+            PieceColor::Black => PieceColor::White,
+        }
+    }
+
+    /// The rank this color's pieces start on
+    pub fn back_rank(self) -> u8 {
+        match self {
+            PieceColor::White => 0,
+            PieceColor::Black => MAX - 1,
+        }
+    }
+
+    fn forward(self) -> i8 {
+        match self {
+            PieceColor::White => 1,
+            PieceColor::Black => -1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PieceType {
+    King,
+    Queen,
+    Bishop,
+    Knight,
+    Rook,
+    Pawn,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct CastlingRights {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool,
+}
+
+impl CastlingRights {
+    pub const ALL: Self = Self {
+        white_king_side: true,
+        white_queen_side: true,
+        black_king_side: true,
+        black_queen_side: true,
+    };
+
+    pub fn get(&self, color: PieceColor, king_side: bool) -> bool {
+        match (color, king_side) {
+            (PieceColor::White, true) => self.white_king_side,
+            (PieceColor::White, false) => self.white_queen_side,
+            (PieceColor::Black, true) => self.black_king_side,
+            (PieceColor::Black, false) => self.black_queen_side,
+        }
+    }
+
+    fn revoke(&mut self, color: PieceColor, king_side: bool) {
+        match (color, king_side) {
+            (PieceColor::White, true) => self.white_king_side = false,
+            (PieceColor::White, false) => self.white_queen_side = false,
+            (PieceColor::Black, true) => self.black_king_side = false,
+            (PieceColor::Black, false) => self.black_queen_side = false,
+        }
+    }
+
+    /// Drops the right tied to the rook starting on `square`, if any
+    fn revoke_corner(&mut self, square: Square) {
+        for color in [PieceColor::White, PieceColor::Black] {
+            if square == (MAX - 1, color.back_rank()) {
+                self.revoke(color, true);
+            } else if square == (0, color.back_rank()) {
+                self.revoke(color, false);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<PieceType>,
+}
+
+impl Move {
+    pub fn new(from: Square, to: Square) -> Self {
+        Self {
+            from,
+            to,
+            promotion: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    Checkmate(PieceColor), // NOTE: The winner
+    Stalemate,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Position {
+    squares: [Option<(PieceColor, PieceType)>; (MAX * MAX) as usize],
+    pub side: PieceColor,
+    pub castling: CastlingRights,
+    /// The square skipped by the last move, when it was a double pawn push
+    pub en_passant: Option<Square>,
+    /// Plies since the last capture or pawn move, for the fifty-move rule
+    pub halfmove_clock: u32,
+    /// Starts at 1 and grows after every Black move
+    pub fullmove_number: u32,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::start()
+    }
+}
+
+impl Position {
+    pub fn empty() -> Self {
+        Self {
+            squares: [None; (MAX * MAX) as usize],
+            side: PieceColor::White,
+            castling: CastlingRights::default(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
+
+    pub fn start() -> Self {
+        let mut position = Self {
+            castling: CastlingRights::ALL,
+            ..Self::empty()
+        };
+
+        let back_rank = [
+            PieceType::Rook,
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Queen,
+            PieceType::King,
+            PieceType::Bishop,
+            PieceType::Knight,
+            PieceType::Rook,
+        ];
+        for (x, my_type) in (0..MAX).zip(back_rank) {
+            for color in [PieceColor::White, PieceColor::Black] {
+                let pawn_rank = (color.back_rank() as i8 + color.forward()) as u8;
+                position.set((x, color.back_rank()), Some((color, my_type)));
+                position.set((x, pawn_rank), Some((color, PieceType::Pawn)));
+            }
+        }
+
+        position
+    }
+
+    pub fn piece_at(&self, square: Square) -> Option<(PieceColor, PieceType)> {
+        self.squares[index(square)]
+    }
+
+    pub fn set(&mut self, square: Square, piece: Option<(PieceColor, PieceType)>) {
+        self.squares[index(square)] = piece;
+    }
+
+    pub fn pieces(&self) -> impl Iterator<Item = (Square, PieceColor, PieceType)> + '_ {
+        self.squares.iter().enumerate().filter_map(|(i, piece)| {
+            piece.map(|(color, my_type)| ((i as u8 % MAX, i as u8 / MAX), color, my_type))
+        })
+    }
+
+    pub fn king(&self, color: PieceColor) -> Option<Square> {
+        self.pieces()
+            .find(|(_, c, my_type)| *c == color && *my_type == PieceType::King)
+            .map(|(square, _, _)| square)
+    }
+
+    pub fn in_check(&self, color: PieceColor) -> bool {
+        self.king(color)
+            .is_some_and(|king| self.attacked(king, color.opposite()))
+    }
+
+    /// Whether any piece of color `by` hits `square`, regardless of what stands on it
+    pub fn attacked(&self, square: Square, by: PieceColor) -> bool {
+        let hits = |steps: &[(i8, i8)], types: &[PieceType], slide: bool| {
+            steps.iter().any(|step| {
+                let mut current = square;
+                while let Some(next) = offset(current, *step) {
+                    match self.piece_at(next) {
+                        Some((color, my_type)) => {
+                            return color == by && types.contains(&my_type);
+                        }
+                        None if slide => current = next,
+                        None => return false,
+                    }
+                }
+                false
+            })
+        };
+
+        // NOTE: A pawn of color `by` attacks from one rank behind, seen from its own side
+        let pawn_steps = [(-1, -by.forward()), (1, -by.forward())];
+
+        hits(&pawn_steps, &[PieceType::Pawn], false)
+            || hits(&KNIGHT_STEPS, &[PieceType::Knight], false)
+            || hits(&KING_STEPS, &[PieceType::King], false)
+            || hits(&ROOK_STEPS, &[PieceType::Rook, PieceType::Queen], true)
+            || hits(&BISHOP_STEPS, &[PieceType::Bishop, PieceType::Queen], true)
+    }
+
+    pub fn is_castling(&self, m: Move) -> bool {
+        self.piece_at(m.from)
+            .is_some_and(|(_, my_type)| my_type == PieceType::King)
+            && m.from.0.abs_diff(m.to.0) == 2
+    }
+
+    pub fn is_en_passant(&self, m: Move) -> bool {
+        self.piece_at(m.from)
+            .is_some_and(|(_, my_type)| my_type == PieceType::Pawn)
+            && m.from.0 != m.to.0
+            && self.piece_at(m.to).is_none()
+    }
+
+    /// Where the piece taken by `m` stands, which differs from `m.to` on en passant
+    pub fn captured_square(&self, m: Move) -> Option<Square> {
+        if self.is_en_passant(m) {
+            Some((m.to.0, m.from.1))
+        } else {
+            self.piece_at(m.to).map(|_| m.to)
+        }
+    }
+}
+
+pub fn legal_moves(position: &Position) -> Vec<Move> {
+    let mut moves = Vec::new();
+    pseudo_legal_moves(position, &mut moves);
+
+    moves.retain(|m| !apply(position, *m).in_check(position.side));
+    moves
+}
+
+pub fn apply(position: &Position, m: Move) -> Position {
+    let mut next = position.clone();
+    let Some((color, my_type)) = position.piece_at(m.from) else {
+        return next;
+    };
+
+    let captured = position.captured_square(m);
+    if let Some(captured) = captured {
+        next.set(captured, None);
+    }
+    next.set(m.from, None);
+    next.set(m.to, Some((color, m.promotion.unwrap_or(my_type))));
+
+    if position.is_castling(m) {
+        let (rook_from, rook_to) = if m.to.0 > m.from.0 {
+            ((MAX - 1, m.from.1), (m.to.0 - 1, m.from.1))
+        } else {
+            ((0, m.from.1), (m.to.0 + 1, m.from.1))
+        };
+        next.set(rook_from, None);
+        next.set(rook_to, Some((color, PieceType::Rook)));
+    }
+
+    if my_type == PieceType::King {
+        next.castling.revoke(color, true);
+        next.castling.revoke(color, false);
+    }
+    next.castling.revoke_corner(m.from);
+    next.castling.revoke_corner(m.to);
+
+    let double_push = my_type == PieceType::Pawn && m.from.1.abs_diff(m.to.1) == 2;
+    next.en_passant = double_push.then_some((m.from.0, (m.from.1 + m.to.1) / 2));
+    next.side = color.opposite();
+
+    if my_type == PieceType::Pawn || captured.is_some() {
+        next.halfmove_clock = 0;
+    } else {
+        next.halfmove_clock += 1;
+    }
+    if color == PieceColor::Black {
+        next.fullmove_number += 1;
+    }
+
+    next
+}
+
+/// How the game ended, if the side to move has no legal move left
+pub fn outcome(position: &Position) -> Option<Outcome> {
+    if !legal_moves(position).is_empty() {
+        return None;
+    }
+
+    if position.in_check(position.side) {
+        Some(Outcome::Checkmate(position.side.opposite()))
+    } else {
+        Some(Outcome::Stalemate)
+    }
+}
+
+/// Counts the leaf nodes of the legal move tree `depth` plies deep, to check the move generation
+pub fn perft(position: &Position, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = legal_moves(position);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    moves
+        .into_iter()
+        .map(|m| perft(&apply(position, m), depth - 1))
+        .sum()
+}
+
+/// Every move following the piece rules, including those leaving the own king in check
+fn pseudo_legal_moves(position: &Position, moves: &mut Vec<Move>) {
+    let side = position.side;
+
+    for (from, color, my_type) in position.pieces() {
+        if color != side {
+            continue;
+        }
+
+        match my_type {
+            PieceType::Pawn => pawn_moves(position, from, moves),
+            PieceType::Knight => step_moves(position, from, &KNIGHT_STEPS, false, moves),
+            PieceType::King => {
+                step_moves(position, from, &KING_STEPS, false, moves);
+                castling_moves(position, from, moves);
+            }
+            PieceType::Rook => step_moves(position, from, &ROOK_STEPS, true, moves),
+            PieceType::Bishop => step_moves(position, from, &BISHOP_STEPS, true, moves),
+            PieceType::Queen => {
+                step_moves(position, from, &ROOK_STEPS, true, moves);
+                step_moves(position, from, &BISHOP_STEPS, true, moves);
+            }
+        }
+    }
+}
+
+fn step_moves(
+    position: &Position,
+    from: Square,
+    steps: &[(i8, i8)],
+    slide: bool,
+    moves: &mut Vec<Move>,
+) {
+    for step in steps {
+        let mut current = from;
+        while let Some(to) = offset(current, *step) {
+            match position.piece_at(to) {
+                Some((color, _)) => {
+                    if color != position.side {
+                        moves.push(Move::new(from, to));
+                    }
+                    break;
+                }
+                None => moves.push(Move::new(from, to)),
+            }
+
+            if !slide {
+                break;
+            }
+            current = to;
+        }
+    }
+}
+
+fn pawn_moves(position: &Position, from: Square, moves: &mut Vec<Move>) {
+    let side = position.side;
+    let forward = side.forward();
+    let start = (side.back_rank() as i8 + forward) as u8;
+    let last = side.opposite().back_rank();
+
+    let mut push = |to: Square| {
+        if to.1 == last {
+            for my_type in PROMOTIONS {
+                moves.push(Move {
+                    promotion: Some(my_type),
+                    ..Move::new(from, to)
+                });
+            }
+        } else {
+            moves.push(Move::new(from, to));
+        }
+    };
+
+    if let Some(one) = offset(from, (0, forward)).filter(|to| position.piece_at(*to).is_none()) {
+        push(one);
+
+        if from.1 == start {
+            if let Some(two) =
+                offset(one, (0, forward)).filter(|to| position.piece_at(*to).is_none())
+            {
+                push(two);
+            }
+        }
+    }
+
+    for side_step in [-1, 1] {
+        let Some(to) = offset(from, (side_step, forward)) else {
+            continue;
+        };
+
+        let enemy = position
+            .piece_at(to)
+            .is_some_and(|(color, _)| color != side);
+        let en_passant = position.en_passant == Some(to) && position.piece_at(to).is_none();
+        if enemy || en_passant {
+            push(to);
+        }
+    }
+}
+
+fn castling_moves(position: &Position, from: Square, moves: &mut Vec<Move>) {
+    let side = position.side;
+    let enemy = side.opposite();
+
+    if from != (4, side.back_rank()) {
+        return;
+    }
+
+    for king_side in [true, false] {
+        let rook = if king_side { MAX - 1 } else { 0 };
+        let rook_ready = position.piece_at((rook, from.1)) == Some((side, PieceType::Rook));
+        if !position.castling.get(side, king_side) || !rook_ready {
+            continue;
+        }
+
+        // NOTE: There should be no pieces between the king and the rook
+        let between = if king_side {
+            from.0 + 1..rook
+        } else {
+            1..from.0
+        };
+        if between
+            .into_iter()
+            .any(|x| position.piece_at((x, from.1)).is_some())
+        {
+            continue;
+        }
+
+        // NOTE: The king may not castle out of, through or into check
+        let to = if king_side { from.0 + 2 } else { from.0 - 2 };
+        let crossed = if king_side { from.0..=to } else { to..=from.0 };
+        if crossed
+            .into_iter()
+            .any(|x| position.attacked((x, from.1), enemy))
+        {
+            continue;
+        }
+
+        moves.push(Move::new(from, (to, from.1)));
+    }
+}
+
+fn index(square: Square) -> usize {
+    (square.1 * MAX + square.0) as usize
+}
+
+fn offset(square: Square, step: (i8, i8)) -> Option<Square> {
+    let x = square.0 as i8 + step.0;
+    let y = square.1 as i8 + step.1;
+    let range = 0..MAX as i8;
+
+    (range.contains(&x) && range.contains(&y)).then_some((x as u8, y as u8))
+}
@@ -0,0 +1,193 @@
+//! Standard Algebraic Notation for `Move`
+
+use crate::fen::{parse_square, square_name};
+use crate::rules::*;
+
+pub fn write(position: &Position, m: Move) -> String {
+    let mut san = write_without_check(position, m);
+
+    let next = apply(position, m);
+    if next.in_check(next.side) {
+        san.push(if legal_moves(&next).is_empty() {
+            '#'
+        } else {
+            '+'
+        });
+    }
+
+    san
+}
+
+/// The SAN of `m` for people rather than files, marking en passant captures with `e.p.`
+pub fn display(position: &Position, m: Move) -> String {
+    let san = write(position, m);
+
+    if position.is_en_passant(m) {
+        format!("{} e.p.", san)
+    } else {
+        san
+    }
+}
+
+/// Finds the legal move written as `san`, also accepting the usual informal spellings
+/// like `0-0`, `e8Q`, `Ng1f3`, `exd6 e.p.` or moves with annotations and missing `x`
+pub fn parse(position: &Position, san: &str) -> Result<Move, String> {
+    let invalid = || format!("'{}' is not a valid move", san);
+
+    let text = san.trim();
+    let text = text.strip_suffix("e.p.").unwrap_or(text).trim_end();
+    let text = text.trim_end_matches(['+', '#', '!', '?']);
+
+    let candidates: Vec<Move> = match text {
+        "O-O" | "0-0" | "O-O-O" | "0-0-0" => {
+            let king_side = text.len() == 3;
+            legal_moves(position)
+                .into_iter()
+                .filter(|m| position.is_castling(*m) && (m.to.0 > m.from.0) == king_side)
+                .collect()
+        }
+        _ => {
+            let (my_type, rest) = match text.chars().next().and_then(letter_piece) {
+                Some(my_type) => (my_type, &text[1..]),
+                None => (PieceType::Pawn, text),
+            };
+
+            let (rest, promotion) = split_promotion(rest).ok_or_else(invalid)?;
+            let rest: String = rest
+                .chars()
+                .filter(|c| !matches!(c, 'x' | ':' | '-'))
+                .collect();
+            if rest.len() < 2 || !rest.is_ascii() {
+                return Err(invalid());
+            }
+
+            let (from, to) = rest.split_at(rest.len() - 2);
+            let to = parse_square(to).map_err(|_| invalid())?;
+            let from_file = from.chars().find(|c| ('a'..='h').contains(c));
+            let from_rank = from.chars().find(|c| ('1'..='8').contains(c));
+            if from.len() != from_file.is_some() as usize + from_rank.is_some() as usize {
+                return Err(invalid());
+            }
+
+            legal_moves(position)
+                .into_iter()
+                .filter(|m| {
+                    let name = square_name(m.from);
+                    m.to == to
+                        && m.promotion == promotion
+                        && position.piece_at(m.from).map(|(_, t)| t) == Some(my_type)
+                        && from_file.is_none_or(|file| name.starts_with(file))
+                        && from_rank.is_none_or(|rank| name.ends_with(rank))
+                })
+                .collect()
+        }
+    };
+
+    match candidates.as_slice() {
+        [m] => Ok(*m),
+        [] => Err(format!("'{}' is not a legal move", san)),
+        _ => Err(format!("'{}' is ambiguous", san)),
+    }
+}
+
+pub fn piece_letter(my_type: PieceType) -> char {
+    match my_type {
+        PieceType::King => 'K',
+        PieceType::Queen => 'Q',
+        PieceType::Bishop => 'B',
+        PieceType::Knight => 'N',
+        PieceType::Rook => 'R',
+        PieceType::Pawn => 'P',
+    }
+}
+
+/// The piece written as the uppercase `letter`, pawns having none
+pub fn letter_piece(letter: char) -> Option<PieceType> {
+    match letter {
+        'K' => Some(PieceType::King),
+        'Q' => Some(PieceType::Queen),
+        'B' => Some(PieceType::Bishop),
+        'N' => Some(PieceType::Knight),
+        'R' => Some(PieceType::Rook),
+        _ => None,
+    }
+}
+
+/// Splits `e8=Q`, `e8Q` or `e8=q` into the move and the promoted piece
+fn split_promotion(text: &str) -> Option<(&str, Option<PieceType>)> {
+    let Some(last) = text.chars().last() else {
+        return Some((text, None));
+    };
+    if !last.is_ascii_alphabetic() || text.len() < 2 {
+        return Some((text, None));
+    }
+
+    let rest = &text[..text.len() - 1];
+    let rest = rest.strip_suffix('=').unwrap_or(rest);
+    let my_type = letter_piece(last.to_ascii_uppercase())?;
+    if my_type == PieceType::King {
+        return None;
+    }
+
+    Some((rest, Some(my_type)))
+}
+
+fn write_without_check(position: &Position, m: Move) -> String {
+    let Some((_, my_type)) = position.piece_at(m.from) else {
+        return String::new();
+    };
+
+    if position.is_castling(m) {
+        return if m.to.0 > m.from.0 { "O-O" } else { "O-O-O" }.to_string();
+    }
+
+    let capture = position.captured_square(m).is_some();
+    let mut san = String::new();
+
+    if my_type == PieceType::Pawn {
+        if capture {
+            san.push(square_name(m.from).remove(0));
+        }
+    } else {
+        san.push(piece_letter(my_type));
+        san.push_str(&disambiguation(position, m, my_type));
+    }
+
+    if capture {
+        san.push('x');
+    }
+    san.push_str(&square_name(m.to));
+
+    if let Some(promotion) = m.promotion {
+        san.push('=');
+        san.push(piece_letter(promotion));
+    }
+
+    san
+}
+
+/// The file, rank or both of `m.from`, as needed to tell it apart from same-type pieces reaching `m.to`
+fn disambiguation(position: &Position, m: Move, my_type: PieceType) -> String {
+    let others: Vec<Square> = legal_moves(position)
+        .into_iter()
+        .filter(|other| {
+            other.to == m.to
+                && other.from != m.from
+                && position
+                    .piece_at(other.from)
+                    .is_some_and(|(_, other_type)| other_type == my_type)
+        })
+        .map(|other| other.from)
+        .collect();
+
+    let name = square_name(m.from);
+    if others.is_empty() {
+        String::new()
+    } else if others.iter().all(|from| from.0 != m.from.0) {
+        name[..1].to_string()
+    } else if others.iter().all(|from| from.1 != m.from.1) {
+        name[1..].to_string()
+    } else {
+        name
+    }
+}
@@ -0,0 +1,319 @@
+//! Iterative-deepening alpha-beta search for the best move of a `Position`
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::eval::{evaluate_with, value, Personality};
+use crate::rules::*;
+
+/// The score of checkmating, less the number of plies it takes
+pub const MATE: i32 = 100_000;
+const INFINITY: i32 = MATE + 1;
+
+/// The deepest iteration, searches without other limits stop there
+pub const MAX_DEPTH: u32 = 64;
+
+/// How often, in nodes, the time and node limits are checked
+const CHECK_INTERVAL: u64 = 1024;
+
+/// When to stop searching, whichever limit comes first
+#[derive(Clone, PartialEq, Debug)]
+pub struct Limits {
+    pub depth: u32,
+    pub time: Option<Duration>,
+    pub nodes: Option<u64>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            depth: 4,
+            time: None,
+            nodes: None,
+        }
+    }
+}
+
+/// How well the search plays within its limits, flawlessly by default
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Skill {
+    /// The most centipawns randomly added to or taken from the score of each move
+    pub noise: i32,
+    /// The chance in percent of playing the second best move instead of the best
+    pub mistakes: u32,
+    pub personality: Personality,
+    /// Seeds the randomness, the same seed replays the same game
+    pub seed: u64,
+}
+
+impl Skill {
+    fn flawless(&self) -> bool {
+        self.noise == 0 && self.mistakes == 0
+    }
+}
+
+/// The outcome of the deepest fully searched iteration
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Report {
+    pub best: Option<Move>,
+    /// Centipawns for the side to move, or `MATE` less the plies to mate
+    pub score: i32,
+    pub depth: u32,
+    pub nodes: u64,
+    pub time: Duration,
+}
+
+pub fn search(position: &Position, limits: &Limits) -> Report {
+    search_with(
+        position,
+        limits,
+        Skill::default(),
+        &AtomicBool::new(false),
+        |_| {},
+    )
+}
+
+/// Searches until a limit is reached or `stop` is set, calling `on_depth` after every iteration
+pub fn search_with(
+    position: &Position,
+    limits: &Limits,
+    skill: Skill,
+    stop: &AtomicBool,
+    mut on_depth: impl FnMut(&Report),
+) -> Report {
+    let mut search = Search {
+        limits,
+        skill,
+        stop,
+        start: Instant::now(),
+        nodes: 0,
+        aborted: false,
+        random: skill.seed | 1,
+    };
+    let mut report = Report::default();
+
+    for depth in 1..=limits.depth.clamp(1, MAX_DEPTH) {
+        let Some((best, score)) = search.root(position, depth, report.best) else {
+            break;
+        };
+
+        report = Report {
+            best: Some(best),
+            score,
+            depth,
+            nodes: search.nodes,
+            time: search.start.elapsed(),
+        };
+        on_depth(&report);
+
+        // NOTE: A deeper search would not find a faster mate, nor finish in the time left
+        let mate_found = score.abs() >= MATE - depth as i32;
+        let half_time = limits.time.is_some_and(|time| report.time * 2 > time);
+        if mate_found || half_time {
+            break;
+        }
+    }
+
+    // NOTE: Stopped before the first iteration finished, any legal move beats none
+    if report.best.is_none() {
+        report.best = ordered_moves(position, None).first().copied();
+        report.nodes = search.nodes;
+        report.time = search.start.elapsed();
+    }
+
+    report
+}
+
+struct Search<'a> {
+    limits: &'a Limits,
+    skill: Skill,
+    stop: &'a AtomicBool,
+    start: Instant,
+    nodes: u64,
+    aborted: bool,
+    /// The state of a xorshift generator, never zero
+    random: u64,
+}
+
+impl Search<'_> {
+    fn root(
+        &mut self,
+        position: &Position,
+        depth: u32,
+        first: Option<Move>,
+    ) -> Option<(Move, i32)> {
+        if !self.skill.flawless() {
+            return self.imperfect_root(position, depth, first);
+        }
+
+        let mut alpha = -INFINITY;
+        let mut best = None;
+
+        for m in ordered_moves(position, first) {
+            let score = -self.negamax(&apply(position, m), depth - 1, 1, -INFINITY, -alpha);
+            if self.aborted {
+                return None;
+            }
+
+            if score > alpha {
+                alpha = score;
+                best = Some(m);
+            }
+        }
+
+        best.map(|m| (m, alpha))
+    }
+
+    /// Scores every move exactly, then blurs the scores with noise and sometimes
+    /// settles for the second best, as a weaker player would
+    fn imperfect_root(
+        &mut self,
+        position: &Position,
+        depth: u32,
+        first: Option<Move>,
+    ) -> Option<(Move, i32)> {
+        let mut scored = Vec::new();
+
+        for m in ordered_moves(position, first) {
+            let score = -self.negamax(&apply(position, m), depth - 1, 1, -INFINITY, INFINITY);
+            if self.aborted {
+                return None;
+            }
+
+            let noise = match self.skill.noise {
+                0 => 0,
+                noise => (self.next_random() % (2 * noise as u64 + 1)) as i32 - noise,
+            };
+            scored.push((m, score, score + noise));
+        }
+
+        scored.sort_by_key(|(_, _, blurred)| std::cmp::Reverse(*blurred));
+        let mistake = self.next_random() % 100 < self.skill.mistakes as u64;
+        let (m, score, _) = if mistake && scored.len() > 1 {
+            scored[1]
+        } else {
+            *scored.first()?
+        };
+
+        Some((m, score))
+    }
+
+    fn next_random(&mut self) -> u64 {
+        self.random ^= self.random << 13;
+        self.random ^= self.random >> 7;
+        self.random ^= self.random << 17;
+        self.random
+    }
+
+    fn negamax(
+        &mut self,
+        position: &Position,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        if depth == 0 {
+            return self.quiesce(position, alpha, beta);
+        }
+        if self.should_stop() || position.halfmove_clock >= 100 {
+            return 0;
+        }
+
+        let moves = ordered_moves(position, None);
+        if moves.is_empty() {
+            return if position.in_check(position.side) {
+                ply - MATE
+            } else {
+                0
+            };
+        }
+
+        for m in moves {
+            let score = -self.negamax(&apply(position, m), depth - 1, ply + 1, -beta, -alpha);
+            if self.aborted {
+                return 0;
+            }
+
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+
+        alpha
+    }
+
+    /// Only searches captures and promotions, so the evaluation is never taken mid exchange
+    fn quiesce(&mut self, position: &Position, mut alpha: i32, beta: i32) -> i32 {
+        if self.should_stop() {
+            return 0;
+        }
+
+        let stand_pat = evaluate_with(position, self.skill.personality);
+        if stand_pat >= beta {
+            return beta;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let moves = ordered_moves(position, None)
+            .into_iter()
+            .filter(|m| position.captured_square(*m).is_some() || m.promotion.is_some());
+        for m in moves {
+            let score = -self.quiesce(&apply(position, m), -beta, -alpha);
+            if self.aborted {
+                return 0;
+            }
+
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+
+        alpha
+    }
+
+    fn should_stop(&mut self) -> bool {
+        self.nodes += 1;
+
+        if !self.aborted && self.nodes.is_multiple_of(CHECK_INTERVAL) {
+            self.aborted = self.stop.load(Ordering::Relaxed)
+                || self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes)
+                || self
+                    .limits
+                    .time
+                    .is_some_and(|time| self.start.elapsed() >= time);
+        }
+
+        self.aborted
+    }
+}
+
+/// The legal moves, `first` then promotions and captures of the most valuable pieces by the least valuable
+fn ordered_moves(position: &Position, first: Option<Move>) -> Vec<Move> {
+    let mut moves = legal_moves(position);
+
+    moves.sort_by_cached_key(|m| {
+        if Some(*m) == first {
+            return i32::MIN;
+        }
+
+        let victim = position
+            .captured_square(*m)
+            .and_then(|square| position.piece_at(square))
+            .map_or(0, |(_, my_type)| value(my_type) * 10);
+        let attacker = position
+            .piece_at(m.from)
+            .map_or(0, |(_, my_type)| value(my_type));
+        let promotion = m.promotion.map_or(0, value);
+
+        if victim + promotion > 0 {
+            -(victim + promotion - attacker / 10)
+        } else {
+            0
+        }
+    });
+
+    moves
+}
@@ -0,0 +1,307 @@
+//! The Universal Chess Interface, to play against engines running as child processes
+
+use std::ffi::OsStr;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::fen::{self, parse_square, square_name};
+use crate::rules::*;
+use crate::san::{letter_piece, piece_letter};
+use crate::search::{Limits, MAX_DEPTH};
+
+/// The time the engine gets to think, as sent with `go`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Go {
+    MoveTime(Duration),
+    Clocks {
+        white: Duration,
+        black: Duration,
+        white_increment: Duration,
+        black_increment: Duration,
+    },
+}
+
+impl Go {
+    pub fn command(&self) -> String {
+        match self {
+            Go::MoveTime(time) => format!("go movetime {}", time.as_millis()),
+            Go::Clocks {
+                white,
+                black,
+                white_increment,
+                black_increment,
+            } => format!(
+                "go wtime {} btime {} winc {} binc {}",
+                white.as_millis(),
+                black.as_millis(),
+                white_increment.as_millis(),
+                black_increment.as_millis()
+            ),
+        }
+    }
+
+    /// The longest `side` may think, a move time or everything left on its clock
+    fn time(&self, side: PieceColor) -> Duration {
+        match *self {
+            Go::MoveTime(time) => time,
+            Go::Clocks {
+                white,
+                black,
+                white_increment,
+                black_increment,
+            } => match side {
+                PieceColor::White => white + white_increment,
+                PieceColor::Black => black + black_increment,
+            },
+        }
+    }
+}
+
+/// How long the engine gets to answer `uci` and `isready`
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+/// How late the engine may answer `go` past its thinking time
+const MOVE_GRACE: Duration = Duration::from_secs(5);
+
+/// `m` in long algebraic notation, like `e2e4` or `e7e8q`
+pub fn move_name(m: Move) -> String {
+    let mut name = square_name(m.from) + &square_name(m.to);
+    if let Some(promotion) = m.promotion {
+        name.push(piece_letter(promotion).to_ascii_lowercase());
+    }
+    name
+}
+
+/// Finds the legal move written as `name` in long algebraic notation
+pub fn parse_move(position: &Position, name: &str) -> Result<Move, String> {
+    let invalid = || format!("'{}' is not a valid move", name);
+    if name.len() < 4 || name.len() > 5 || !name.is_ascii() {
+        return Err(invalid());
+    }
+
+    let from = parse_square(&name[0..2]).map_err(|_| invalid())?;
+    let to = parse_square(&name[2..4]).map_err(|_| invalid())?;
+    let promotion = match name[4..].chars().next() {
+        Some(letter) => Some(letter_piece(letter.to_ascii_uppercase()).ok_or_else(invalid)?),
+        None => None,
+    };
+
+    let m = Move {
+        from,
+        to,
+        promotion,
+    };
+    if legal_moves(position).contains(&m) {
+        Ok(m)
+    } else {
+        Err(format!("'{}' is not a legal move", name))
+    }
+}
+
+/// The `position` command for `moves` played from `start`
+pub fn position_command(start: &Position, moves: &[Move]) -> String {
+    let mut command = if *start == Position::start() {
+        "position startpos".to_string()
+    } else {
+        format!("position fen {}", fen::write(start))
+    };
+
+    if !moves.is_empty() {
+        command.push_str(" moves");
+        for m in moves {
+            command.push(' ');
+            command.push_str(&move_name(*m));
+        }
+    }
+
+    command
+}
+
+/// Reads the arguments of a `position` command, `startpos` or `fen <FEN>` then the optional `moves`
+pub fn parse_position(arguments: &str) -> Result<Position, String> {
+    let (setup, moves) = match arguments.split_once("moves") {
+        Some((setup, moves)) => (setup.trim(), moves),
+        None => (arguments.trim(), ""),
+    };
+
+    let mut position = match setup.strip_prefix("fen") {
+        Some(text) => fen::parse(text.trim())?,
+        None if setup == "startpos" => Position::start(),
+        None => return Err(format!("invalid position '{}'", setup)),
+    };
+    for name in moves.split_whitespace() {
+        let m = parse_move(&position, name)?;
+        position = apply(&position, m);
+    }
+
+    Ok(position)
+}
+
+/// The search limits for the arguments of a `go` command, sharing the clock of `side`
+/// out over the moves to go, or the 30 moves a game is assumed to last without them
+pub fn parse_go(arguments: &str, side: PieceColor) -> Limits {
+    let mut limits = Limits {
+        depth: MAX_DEPTH,
+        time: None,
+        nodes: None,
+    };
+    let mut clock = None;
+    let mut increment = Duration::ZERO;
+    let mut moves_to_go = 30;
+
+    // NOTE: `infinite` and `ponder` take no value and keep the default limits
+    let words: Vec<&str> = arguments.split_whitespace().collect();
+    for pair in words.windows(2) {
+        let Ok(value) = pair[1].parse::<u64>() else {
+            continue;
+        };
+        let millis = Duration::from_millis(value);
+
+        match (pair[0], side) {
+            ("depth", _) => limits.depth = value.clamp(1, MAX_DEPTH as u64) as u32,
+            ("nodes", _) => limits.nodes = Some(value),
+            ("movetime", _) => limits.time = Some(millis),
+            ("movestogo", _) => moves_to_go = value.max(1) as u32,
+            ("wtime", PieceColor::White) | ("btime", PieceColor::Black) => clock = Some(millis),
+            ("winc", PieceColor::White) | ("binc", PieceColor::Black) => increment = millis,
+            _ => {}
+        }
+    }
+
+    if let (None, Some(clock)) = (limits.time, clock) {
+        limits.time = Some((clock / moves_to_go + increment / 2).min(clock / 2));
+    }
+
+    limits
+}
+
+/// A UCI engine running as a child process, quit when dropped
+pub struct Engine {
+    child: Child,
+    stdin: ChildStdin,
+    /// The lines of the engine's output, read on their own thread so waiting for them can time out
+    lines: Receiver<String>,
+    pub name: Option<String>,
+}
+
+impl Engine {
+    /// Starts the executable at `path` and waits for it to be ready
+    pub fn start(path: impl AsRef<OsStr>) -> io::Result<Self> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(io::Error::other("no pipes to the engine"));
+        };
+
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line.trim().to_string()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = Self {
+            child,
+            stdin,
+            lines,
+            name: None,
+        };
+
+        engine.send("uci")?;
+        let deadline = Instant::now() + READY_TIMEOUT;
+        loop {
+            let line = engine.read_line(deadline, "uciok")?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = Some(name.to_string());
+            } else if line == "uciok" {
+                break;
+            }
+        }
+        engine.wait_ready()?;
+
+        Ok(engine)
+    }
+
+    pub fn send(&mut self, command: &str) -> io::Result<()> {
+        writeln!(self.stdin, "{}", command)?;
+        self.stdin.flush()
+    }
+
+    pub fn new_game(&mut self) -> io::Result<()> {
+        self.send("ucinewgame")?;
+        self.wait_ready()
+    }
+
+    /// Asks for the move to play after `moves` from `start`, waiting for its answer
+    pub fn best_move(&mut self, start: &Position, moves: &[Move], go: Go) -> io::Result<Move> {
+        self.send(&position_command(start, moves))?;
+        self.send(&go.command())?;
+
+        let position = moves
+            .iter()
+            .fold(start.clone(), |position, m| apply(&position, *m));
+
+        let deadline = Instant::now() + go.time(position.side) + MOVE_GRACE;
+        let answer = loop {
+            let line = self.read_line(deadline, "bestmove")?;
+            if let Some(answer) = line.strip_prefix("bestmove") {
+                break answer.trim().to_string();
+            }
+        };
+
+        let name = answer.split_whitespace().next().unwrap_or_default();
+        parse_move(&position, name)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    fn wait_ready(&mut self) -> io::Result<()> {
+        self.send("isready")?;
+        let deadline = Instant::now() + READY_TIMEOUT;
+        while self.read_line(deadline, "readyok")? != "readyok" {}
+        Ok(())
+    }
+
+    /// The next line of output, the engine is killed when none comes before `deadline`
+    fn read_line(&mut self, deadline: Instant, awaited: &str) -> io::Result<String> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => Ok(line),
+            Err(RecvTimeoutError::Disconnected) => Err(io::ErrorKind::UnexpectedEof.into()),
+            Err(RecvTimeoutError::Timeout) => {
+                let _ = self.child.kill();
+                let _ = self.child.wait();
+                Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("no '{}' from the engine in time", awaited),
+                ))
+            }
+        }
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+
+        // NOTE: Engines ignoring `quit` are killed after a second
+        for _ in 0..100 {
+            if !matches!(self.child.try_wait(), Ok(None)) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
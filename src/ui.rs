@@ -1,81 +1,363 @@
-use crate::{board::*, pieces::*};
-use bevy::prelude::*;
+use super::*;
 
-// Component to mark the Text entity
-struct NextMoveText;
+/// The HUD around the board: turn indicator, captured pieces, clocks, game status and the flip button,
+/// with all the interface scaled like the board
+pub struct UIPlugin;
+impl Plugin for UIPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Startup,
+            (
+                spawn_turn_text,
+                spawn_captured_trays,
+                spawn_clock_texts,
+                spawn_status_banner,
+                spawn_flip_button,
+                spawn_move_input_text,
+                fit_to_window,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                update_turn_text,
+                update_captured_trays.run_if(resource_changed::<Game>()),
+                update_clock_texts.run_if(resource_exists::<Clocks>()),
+                update_status_banner,
+                press_flip_button,
+                orient_hud.run_if(resource_changed::<Orientation>()),
+                fit_to_window.run_if(on_event::<WindowResized>()),
+            ),
+        );
+    }
+}
 
-/// Initialize UiCamera and text
-fn init_next_move_text(
-    mut commands: Commands,
-    asset_server: ResMut<AssetServer>,
-    mut color_materials: ResMut<Assets<ColorMaterial>>,
-) {
-    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
-    let material = color_materials.add(Color::NONE.into());
+#[derive(Component)]
+struct TurnText;
 
-    commands
-        .spawn_bundle(UiCameraBundle::default())
-        // root node
-        .commands()
-        .spawn_bundle(NodeBundle {
+/// The pieces of `.0` taken by the other player
+#[derive(Component)]
+struct CapturedTray(PieceColor);
+
+/// The time left to the player of `.0`
+#[derive(Component)]
+struct ClockText(PieceColor);
+
+#[derive(Component)]
+struct StatusBanner;
+
+#[derive(Component)]
+struct FlipButton;
+
+/// The move being typed, or the message answering it
+#[derive(Component)]
+pub struct MoveInputText;
+
+fn spawn_turn_text(mut commands: Commands) {
+    commands.spawn((
+        TextBundle {
+            text: Text {
+                sections: vec![TextSection::default()],
+                alignment: TextAlignment::Center,
+                ..default()
+            },
+            style: Style {
+                position_type: PositionType::Absolute,
+                align_self: AlignSelf::Center,
+                align_content: AlignContent::Center,
+                align_items: AlignItems::Center,
+
+                bottom: Val::Percent(90.0),
+                right: Val::Percent(35.0),
+                left: Val::Percent(35.0),
+
+                width: Val::Percent(30.0),
+                ..default()
+            },
+            ..default()
+        },
+        TurnText,
+    ));
+}
+
+fn spawn_captured_trays(mut commands: Commands) {
+    for color in [PieceColor::White, PieceColor::Black] {
+        commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(3.0),
+                    width: Val::Percent(16.0),
+                    height: Val::Percent(10.0),
+                    flex_wrap: FlexWrap::Wrap,
+                    align_content: AlignContent::FlexStart,
+                    ..default()
+                },
+                ..default()
+            },
+            CapturedTray(color),
+        ));
+    }
+}
+
+fn spawn_clock_texts(mut commands: Commands) {
+    for color in [PieceColor::Black, PieceColor::White] {
+        commands.spawn((
+            TextBundle {
+                text: Text::from_section("", TextStyle::default()),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(3.0),
+                    padding: UiRect::horizontal(Val::Px(HALF_SIZE * 0.25)),
+                    ..default()
+                },
+                ..default()
+            },
+            ClockText(color),
+        ));
+    }
+}
+
+fn spawn_status_banner(mut commands: Commands) {
+    commands.spawn((
+        TextBundle {
+            text: Text {
+                sections: vec![TextSection::default()],
+                alignment: TextAlignment::Center,
+                ..default()
+            },
             style: Style {
                 position_type: PositionType::Absolute,
-                position: Rect {
-                    left: Val::Px(10.),
-                    top: Val::Px(10.),
-                    ..Default::default()
+                align_self: AlignSelf::Center,
+
+                top: Val::Percent(86.0),
+                right: Val::Percent(35.0),
+                left: Val::Percent(35.0),
+
+                width: Val::Percent(30.0),
+                ..default()
+            },
+            background_color: Color::NONE.into(),
+            ..default()
+        },
+        StatusBanner,
+    ));
+}
+
+fn spawn_flip_button(mut commands: Commands) {
+    commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(3.0),
+                    left: Val::Percent(20.0),
+                    padding: UiRect::all(Val::Px(HALF_SIZE * 0.25)),
+                    ..default()
                 },
-                ..Default::default()
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.75).into(),
+                ..default()
             },
-            material,
-            ..Default::default()
-        })
+            FlipButton,
+        ))
         .with_children(|parent| {
-            parent
-                .spawn_bundle(TextBundle {
-                    text: Text::with_section(
-                        "Next move: White",
-                        TextStyle {
-                            font: font.clone(),
-                            font_size: 40.0,
-                            color: Color::rgb(0.8, 0.8, 0.8),
-                        },
-                        Default::default(),
-                    ),
-                    ..Default::default()
-                })
-                .insert(NextMoveText);
+            parent.spawn(TextBundle::from_section(
+                "Flip board",
+                TextStyle {
+                    font_size: HALF_SIZE * 0.5,
+                    ..default()
+                },
+            ));
         });
 }
 
-/// Update text with the correct turn
-fn next_move_text_update(turn: Res<PlayerTurn>, mut query: Query<(&mut Text, &NextMoveText)>) {
-    if !turn.is_changed() {
+fn spawn_move_input_text(mut commands: Commands) {
+    commands.spawn((
+        TextBundle {
+            text: Text {
+                sections: vec![TextSection {
+                    value: String::new(),
+                    style: TextStyle {
+                        font_size: HALF_SIZE * 0.75,
+                        ..default()
+                    },
+                }],
+                alignment: TextAlignment::Center,
+                ..default()
+            },
+            style: Style {
+                position_type: PositionType::Absolute,
+                align_self: AlignSelf::Center,
+
+                top: Val::Percent(92.0),
+                right: Val::Percent(35.0),
+                left: Val::Percent(35.0),
+
+                width: Val::Percent(30.0),
+                ..default()
+            },
+            ..default()
+        },
+        MoveInputText,
+    ));
+}
+
+fn press_flip_button(
+    mut orientation: ResMut<Orientation>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<FlipButton>)>,
+) {
+    if buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        orientation.flip();
+    }
+}
+
+/// Keeps each player's clock and the pieces they took on their own side of the board
+fn orient_hud(
+    mut clocks: Query<(&mut Style, &ClockText), Without<CapturedTray>>,
+    mut trays: Query<(&mut Style, &CapturedTray), Without<ClockText>>,
+    orientation: Res<Orientation>,
+) {
+    for (mut style, clock_text) in clocks.iter_mut() {
+        let bottom = clock_text.0 == orientation.0;
+        style.top = Val::Percent(if bottom { 91.0 } else { 3.0 });
+    }
+
+    // NOTE: The tray of a color holds the pieces taken by the other player
+    for (mut style, tray) in trays.iter_mut() {
+        let bottom = tray.0 != orientation.0;
+        style.top = Val::Percent(if bottom { 80.0 } else { 10.0 });
+    }
+}
+
+/// Scales the interface by as much as the camera scales the board, so `SIZE` pixels stay one square
+fn fit_to_window(mut ui_scale: ResMut<UiScale>, windows: Query<&Window>) {
+    let window = windows.single();
+    let scale = (window.width() / VIEW.x).min(window.height() / VIEW.y);
+    ui_scale.0 = scale as f64;
+}
+
+fn update_turn_text(
+    mut query: Query<&mut Text, With<TurnText>>,
+    turn_manager: Res<TurnManager>,
+    game: Res<Game>,
+) {
+    if !turn_manager.is_changed() && !game.is_changed() {
         return;
     }
-    for (mut text, _tag) in query.iter_mut() {
-        text.sections[0].value = format!(
-            "Next move: {}",
-            match turn.0 {
-                PieceColor::White => "White",
-                PieceColor::Black => "Black",
-            }
-        );
+
+    let over = rules::outcome(&game.position).is_some();
+    for mut text in query.iter_mut() {
+        text.sections[0].value = if over {
+            String::new()
+        } else {
+            format!("{:?} player turn", turn_manager.color())
+        };
+        text.sections[0].style.font_size = SIZE * 0.75;
     }
 }
 
-/// Demo system to show off Query transformers
-fn log_text_changes(query: Query<&Text, Changed<Text>>) {
-    for text in query.iter() {
-        println!("New text: {}", text.sections[0].value);
+/// Shows `minutes:seconds`, with tenths in the last ten seconds, the running clock highlighted
+fn update_clock_texts(
+    mut query: Query<(&mut Text, &mut BackgroundColor, &ClockText)>,
+    clocks: Res<Clocks>,
+) {
+    for (mut text, mut background, clock_text) in query.iter_mut() {
+        let left = clocks.0.remaining(clock_text.0);
+        let seconds = left.as_secs();
+
+        text.sections[0].value = if seconds < 10 {
+            format!("0:{:02}.{}", seconds, left.subsec_millis() / 100)
+        } else {
+            format!("{}:{:02}", seconds / 60, seconds % 60)
+        };
+        text.sections[0].style.font_size = SIZE * 0.6;
+        text.sections[0].style.color = if clocks.0.flagged() == Some(clock_text.0) {
+            Color::RED
+        } else {
+            Color::WHITE
+        };
+
+        *background = if clocks.0.side == clock_text.0 {
+            Color::rgba(0.0, 0.0, 0.0, 0.75).into()
+        } else {
+            Color::NONE.into()
+        };
     }
 }
 
-pub struct UIPlugin;
-impl Plugin for UIPlugin {
-    fn build(&self, app: &mut AppBuilder) {
-        app.add_startup_system(init_next_move_text.system())
-            .add_system(next_move_text_update.system())
-            .add_system(log_text_changes.system());
+/// Fills each tray with the pieces taken up to the shown position, most valuable first
+fn update_captured_trays(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    trays: Query<(Entity, &CapturedTray)>,
+    game: Res<Game>,
+) {
+    let shown = game.viewed.unwrap_or(game.moves.len());
+
+    let mut captured = Vec::new();
+    let mut position = game.start.clone();
+    for m in &game.moves[..shown] {
+        if let Some(piece) = position
+            .captured_square(*m)
+            .and_then(|square| position.piece_at(square))
+        {
+            captured.push(piece);
+        }
+        position = rules::apply(&position, *m);
+    }
+    captured.sort_by_key(|(_, my_type)| std::cmp::Reverse(eval::value(*my_type)));
+
+    for (id, tray) in trays.iter() {
+        commands.entity(id).despawn_descendants();
+        commands.entity(id).with_children(|parent| {
+            for (color, my_type) in captured.iter().filter(|(color, _)| *color == tray.0) {
+                parent.spawn(ImageBundle {
+                    style: Style {
+                        width: Val::Px(HALF_SIZE),
+                        height: Val::Px(HALF_SIZE),
+                        ..default()
+                    },
+                    image: UiImage::new(asset_server.load(sprite_path(*color, *my_type))),
+                    ..default()
+                });
+            }
+        });
+    }
+}
+
+/// Shows check, the end of the game, or that an earlier position is being viewed
+fn update_status_banner(
+    mut query: Query<(&mut Text, &mut BackgroundColor), With<StatusBanner>>,
+    game: Res<Game>,
+    clocks: Option<Res<Clocks>>,
+) {
+    let flagged = clocks.as_ref().and_then(|clocks| clocks.0.flagged());
+    let flag_fell = clocks.is_some_and(|clocks| clocks.is_changed() && flagged.is_some());
+    if !game.is_changed() && !flag_fell {
+        return;
+    }
+
+    let status = match (game.viewed, rules::outcome(&game.position), flagged) {
+        (Some(ply), _, _) => format!("Viewing move {}, press End to return", ply),
+        (None, Some(rules::Outcome::Checkmate(winner)), _) => {
+            format!("{:?} wins by checkmate", winner)
+        }
+        (None, Some(rules::Outcome::Stalemate), _) => "Draw by stalemate".to_string(),
+        (None, None, Some(loser)) => format!("{:?} wins on time", loser.opposite()),
+        (None, None, None) if game.position.in_check(game.position.side) => "Check".to_string(),
+        (None, None, None) => String::new(),
+    };
+
+    for (mut text, mut background) in query.iter_mut() {
+        *background = if status.is_empty() {
+            Color::NONE.into()
+        } else {
+            Color::rgba(0.0, 0.0, 0.0, 0.75).into()
+        };
+        text.sections[0].value = status.clone();
+        text.sections[0].style.font_size = HALF_SIZE * 0.75;
     }
 }
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use bevy_project::clock::{self, Bonus, Clock, TimeControl};
+use bevy_project::rules::PieceColor;
+
+fn secs(seconds: u64) -> Duration {
+    Duration::from_secs(seconds)
+}
+
+#[test]
+fn parses_presets_and_custom_controls() {
+    assert_eq!(clock::parse("blitz").unwrap(), TimeControl::blitz());
+    assert_eq!(clock::parse("5").unwrap(), TimeControl::new(5, 0));
+    assert_eq!(
+        clock::parse("5+3").unwrap().bonus,
+        Bonus::Increment(secs(3))
+    );
+    assert_eq!(
+        clock::parse("5+3d").unwrap().bonus,
+        Bonus::SimpleDelay(secs(3))
+    );
+    assert_eq!(
+        clock::parse("5+3b").unwrap().bonus,
+        Bonus::Bronstein(secs(3))
+    );
+
+    for invalid in ["", "0+1", "five", "5+x", "5+3q"] {
+        assert!(clock::parse(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn only_the_side_to_move_runs() {
+    let mut clock = Clock::new(TimeControl::new(1, 0), PieceColor::White);
+
+    clock.tick(secs(10));
+    clock.press();
+    clock.tick(secs(5));
+
+    assert_eq!(clock.remaining(PieceColor::White), secs(50));
+    assert_eq!(clock.remaining(PieceColor::Black), secs(55));
+}
+
+#[test]
+fn bonuses() {
+    let control = |bonus| TimeControl {
+        base: secs(60),
+        bonus,
+    };
+    let cases = [
+        (Bonus::Increment(secs(5)), secs(62)),
+        (Bonus::SimpleDelay(secs(5)), secs(60)),
+        (Bonus::Bronstein(secs(5)), secs(60)),
+        (Bonus::None, secs(57)),
+    ];
+
+    for (bonus, expected) in cases {
+        let mut clock = Clock::new(control(bonus), PieceColor::White);
+        clock.tick(secs(3));
+        clock.press();
+        assert_eq!(clock.remaining(PieceColor::White), expected, "{:?}", bonus);
+    }
+
+    // NOTE: Delays never give back more than they hold
+    let mut clock = Clock::new(control(Bonus::Bronstein(secs(5))), PieceColor::White);
+    clock.tick(secs(20));
+    clock.press();
+    assert_eq!(clock.remaining(PieceColor::White), secs(45));
+
+    let mut clock = Clock::new(control(Bonus::SimpleDelay(secs(5))), PieceColor::White);
+    clock.tick(secs(20));
+    assert_eq!(clock.remaining(PieceColor::White), secs(45));
+}
+
+#[test]
+fn flag_falls_once_time_runs_out() {
+    let mut clock = Clock::new(TimeControl::bullet(), PieceColor::Black);
+
+    assert_eq!(clock.tick(secs(59)), None);
+    assert_eq!(clock.tick(secs(2)), Some(PieceColor::Black));
+    assert_eq!(clock.remaining(PieceColor::Black), Duration::ZERO);
+
+    // NOTE: The clock stops with the flag
+    clock.press();
+    assert_eq!(clock.side, PieceColor::Black);
+    assert_eq!(clock.tick(secs(1)), Some(PieceColor::Black));
+}
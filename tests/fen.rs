@@ -0,0 +1,65 @@
+use bevy_project::{fen, rules};
+
+#[test]
+fn start_position_matches() {
+    assert_eq!(fen::parse(fen::START).unwrap(), rules::Position::start());
+    assert_eq!(fen::write(&rules::Position::start()), fen::START);
+}
+
+#[test]
+fn round_trip() {
+    let positions = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 12 40",
+    ];
+
+    for fen in positions {
+        assert_eq!(fen::write(&fen::parse(fen).unwrap()), fen);
+    }
+}
+
+#[test]
+fn moves_update_every_field() {
+    let mut position = rules::Position::start();
+    for (from, to) in [((4, 1), (4, 3)), ((6, 7), (5, 5)), ((4, 0), (4, 1))] {
+        position = rules::apply(&position, rules::Move::new(from, to));
+    }
+
+    assert_eq!(
+        fen::write(&position),
+        "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPPKPPP/RNBQ1BNR b kq - 2 2"
+    );
+}
+
+#[test]
+fn rejects_invalid_input() {
+    let invalid = [
+        "",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/ppppxppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq z9 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - zero 1",
+        "999999999999999999999999999999/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/08/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/45/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1R w KQkq - 0 1",
+        // NOTE: En passant squares that no pawn skipped, or that are not empty
+        "4k3/8/8/8/8/3N4/2P5/4K3 w - d3 0 1",
+        "4k3/8/8/8/8/8/8/4K3 w - e6 0 1",
+        "4k3/8/4p3/4p3/8/8/8/4K3 w - e6 0 1",
+        "4k3/8/8/4p3/8/8/8/4K3 b - e6 0 1",
+        // NOTE: The side not to move in check, and pawns on the first or last rank
+        "4k3/4R3/8/8/8/8/8/4K3 w - - 0 1",
+        "4k2P/8/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/p3K3 b - - 0 1",
+    ];
+
+    for fen in invalid {
+        assert!(fen::parse(fen).is_err(), "{}", fen);
+    }
+}
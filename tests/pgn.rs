@@ -0,0 +1,85 @@
+use bevy_project::{pgn, rules};
+
+const OPERA_GAME: &str = r#"[Event "Paris"]
+[Site "Paris FRA"]
+[Date "1858.??.??"]
+[Round "?"]
+[White "Paul Morphy"]
+[Black "Duke Karl / Count Isouard"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 d6 3. d4 Bg4 4. dxe5 Bxf3 5. Qxf3 dxe5 6. Bc4 Nf6 7. Qb3 Qe7 8.
+Nc3 c6 9. Bg5 b5 10. Nxb5 cxb5 11. Bxb5+ Nbd7 12. O-O-O Rd8 13. Rxd7 Rxd7 14.
+Rd1 Qe6 15. Bxd7+ Nxd7 16. Qb8+ Nxb8 17. Rd8# 1-0
+"#;
+
+#[test]
+fn round_trip() {
+    let record = pgn::parse(OPERA_GAME).unwrap();
+
+    assert_eq!(record.moves.len(), 33);
+    assert_eq!(record.tag("White"), Some("Paul Morphy"));
+    assert_eq!(pgn::result(&record.position()), "1-0");
+    assert_eq!(pgn::write(&record), OPERA_GAME);
+}
+
+#[test]
+fn skips_comments_variations_and_glyphs() {
+    let text = "1. e4 {best by test} e5 (1... c5 2. Nf3 (2. c3) d6) 2. Nf3 $1 ; mainline\n\
+                Nc6 3. Bb5!? a6 *";
+    let record = pgn::parse(text).unwrap();
+
+    let expected = pgn::parse("1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 *").unwrap();
+    assert_eq!(record.moves, expected.moves);
+}
+
+#[test]
+fn castles_written_with_zeros() {
+    let record = pgn::parse("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0 *").unwrap();
+
+    let expected = pgn::parse("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. O-O *").unwrap();
+    assert_eq!(record.moves, expected.moves);
+}
+
+#[test]
+fn skips_en_passant_marks() {
+    let record = pgn::parse("1. e4 Nf6 2. e5 d5 3. exd6 e.p. Qxd6 *").unwrap();
+
+    let expected = pgn::parse("1. e4 Nf6 2. e5 d5 3. exd6 Qxd6 *").unwrap();
+    assert_eq!(record.moves, expected.moves);
+}
+
+#[test]
+fn starts_from_fen_tag() {
+    let text = r#"[Event "?"]
+[Site "?"]
+[Date "????.??.??"]
+[Round "?"]
+[White "?"]
+[Black "?"]
+[Result "*"]
+[SetUp "1"]
+[FEN "4k3/1P6/8/8/8/8/8/4K3 w - - 0 60"]
+
+60. b8=Q+ Kd7 *
+"#;
+    let record = pgn::parse(text).unwrap();
+
+    assert_ne!(record.start, rules::Position::start());
+    assert_eq!(pgn::write(&record), text);
+}
+
+#[test]
+fn new_record_fills_seven_tag_roster() {
+    let record = pgn::Record::new(rules::Position::start(), Vec::new());
+
+    for name in pgn::SEVEN_TAG_ROSTER {
+        assert!(record.tag(name).is_some(), "{}", name);
+    }
+    assert_eq!(record.tag("Result"), Some("*"));
+}
+
+#[test]
+fn rejects_illegal_moves() {
+    assert!(pgn::parse("1. e4 e5 2. Ke3 *").is_err());
+}
@@ -0,0 +1,95 @@
+use bevy_project::{fen, rules, san};
+
+fn position(fen: &str) -> rules::Position {
+    fen::parse(fen).unwrap()
+}
+
+fn round_trip(fen: &str, san: &str) {
+    let position = position(fen);
+    let m = san::parse(&position, san).unwrap();
+
+    assert_eq!(san::write(&position, m), san);
+}
+
+#[test]
+fn writes_piece_and_pawn_moves() {
+    round_trip(fen::START, "e4");
+    round_trip(fen::START, "Nf3");
+    round_trip(
+        "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+        "exd5",
+    );
+}
+
+#[test]
+fn disambiguates_by_file_rank_or_both() {
+    let fen = "3k4/8/8/8/8/8/4K3/R6R w - - 0 1";
+    round_trip(fen, "Rab1");
+    round_trip(fen, "Rhf1");
+
+    round_trip("7k/8/8/8/R7/8/8/R3K3 w - - 0 1", "R1a3");
+    round_trip("6k1/8/8/8/8/8/8/Q1Q1K3 w - - 0 1", "Qcb1");
+    round_trip("8/8/7k/8/Q1Q5/8/Q7/4K3 w - - 0 1", "Qa4b3");
+}
+
+#[test]
+fn writes_castling_promotion_and_checks() {
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    round_trip(fen, "O-O");
+    round_trip(fen, "O-O-O");
+
+    round_trip("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b8=Q+");
+    round_trip("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b8=N");
+    round_trip("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "Ra8#");
+}
+
+#[test]
+fn displays_en_passant() {
+    let position = position("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+    let m = san::parse(&position, "exf6").unwrap();
+
+    assert_eq!(san::write(&position, m), "exf6");
+    assert_eq!(san::display(&position, m), "exf6 e.p.");
+    assert_eq!(san::parse(&position, "exf6 e.p."), Ok(m));
+}
+
+#[test]
+fn parses_informal_spellings() {
+    let castling = position("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    assert_eq!(san::parse(&castling, "0-0"), san::parse(&castling, "O-O"));
+    assert_eq!(
+        san::parse(&castling, "0-0-0"),
+        san::parse(&castling, "O-O-O")
+    );
+
+    let promotion = position("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+    assert_eq!(
+        san::parse(&promotion, "b8Q"),
+        san::parse(&promotion, "b8=Q")
+    );
+    assert_eq!(
+        san::parse(&promotion, "b8=q"),
+        san::parse(&promotion, "b8=Q")
+    );
+
+    let start = position(fen::START);
+    assert_eq!(san::parse(&start, "Ng1f3"), san::parse(&start, "Nf3"));
+    assert_eq!(san::parse(&start, "Ng1-f3!?"), san::parse(&start, "Nf3"));
+}
+
+#[test]
+fn rejects_illegal_ambiguous_and_garbage() {
+    let start = position(fen::START);
+    assert!(san::parse(&start, "e5").is_err());
+    assert!(san::parse(&start, "Ke2").is_err());
+    assert!(san::parse(&start, "").is_err());
+    assert!(san::parse(&start, "hello").is_err());
+
+    let rooks = position("3k4/8/8/8/8/8/4K3/R6R w - - 0 1");
+    assert!(san::parse(&rooks, "Ra2").is_ok());
+    assert!(san::parse(&rooks, "Rd1").is_err());
+
+    let promotion = position("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+    assert!(san::parse(&promotion, "b8").is_err());
+    assert!(san::parse(&promotion, "b8=K").is_err());
+}
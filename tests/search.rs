@@ -0,0 +1,133 @@
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use bevy_project::search::{self, Limits, Skill, MATE};
+use bevy_project::{eval, fen, rules, san};
+
+fn best_san(fen: &str, depth: u32) -> String {
+    let position = fen::parse(fen).unwrap();
+    let limits = Limits {
+        depth,
+        ..Limits::default()
+    };
+
+    let best = search::search(&position, &limits).best.unwrap();
+    san::write(&position, best)
+}
+
+#[test]
+fn start_position_is_balanced() {
+    assert_eq!(eval::evaluate(&rules::Position::start()), 0);
+}
+
+#[test]
+fn finds_mate_in_one() {
+    assert_eq!(best_san("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 2), "Ra8#");
+    assert_eq!(best_san("r5k1/8/8/8/8/8/5PPP/6K1 b - - 0 1", 2), "Ra1#");
+}
+
+#[test]
+fn takes_hanging_pieces_and_avoids_losing_them() {
+    assert_eq!(best_san("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", 2), "Rxd5");
+    // NOTE: Taking the defended pawn loses the queen
+    assert_ne!(best_san("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1", 3), "Qxd5");
+}
+
+#[test]
+fn reports_mate_scores() {
+    let position = fen::parse("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    let report = search::search(&position, &Limits::default());
+
+    assert_eq!(report.score, MATE - 1);
+    assert_eq!(report.depth, 2);
+}
+
+#[test]
+fn stops_at_its_limits() {
+    let position = rules::Position::start();
+
+    let limits = Limits {
+        depth: 64,
+        time: Some(Duration::from_millis(200)),
+        nodes: None,
+    };
+    let report = search::search(&position, &limits);
+    assert!(report.best.is_some());
+    assert!(report.time < Duration::from_secs(2));
+
+    let limits = Limits {
+        depth: 64,
+        time: None,
+        nodes: Some(5_000),
+    };
+    assert!(search::search(&position, &limits).nodes < 5_000 + 1024);
+
+    let stop = AtomicBool::new(true);
+    let report = search::search_with(&position, &limits, Skill::default(), &stop, |_| {});
+    assert!(rules::legal_moves(&position).contains(&report.best.unwrap()));
+}
+
+#[test]
+fn weaker_skills_stray_from_the_best_move() {
+    let position = fen::parse("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+    let limits = Limits {
+        depth: 2,
+        ..Limits::default()
+    };
+    let play = |skill: Skill| {
+        search::search_with(&position, &limits, skill, &AtomicBool::new(false), |_| {}).best
+    };
+
+    let sloppy = Skill {
+        noise: 2_000,
+        seed: 7,
+        ..Skill::default()
+    };
+    assert_eq!(
+        play(sloppy),
+        play(sloppy),
+        "the same seed plays the same move"
+    );
+
+    let strays = (0..20).any(|seed| play(Skill { seed, ..sloppy }) != play(Skill::default()));
+    assert!(strays);
+
+    let careless = Skill {
+        mistakes: 100,
+        ..Skill::default()
+    };
+    assert_ne!(play(careless), play(Skill::default()));
+}
+
+#[test]
+fn difficulties_and_personalities() {
+    use bevy_project::difficulty::Difficulty;
+    use bevy_project::eval::Personality;
+
+    assert_eq!("expert".parse(), Ok(Difficulty::Expert));
+    assert_eq!("Aggressive".parse(), Ok(Personality::Aggressive));
+    assert!("impossible".parse::<Difficulty>().is_err());
+
+    let depths: Vec<u32> = Difficulty::ALL
+        .iter()
+        .map(|difficulty| difficulty.limits().depth)
+        .collect();
+    assert!(depths.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(
+        Difficulty::Master.skill(Personality::Balanced, 0),
+        Skill::default()
+    );
+
+    // NOTE: The aggressive personality values a knight near the black king more than the others do
+    let near = fen::parse("4k3/8/4N3/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let far = fen::parse("4k3/8/8/8/8/8/8/N3K3 w - - 0 1").unwrap();
+    let gain = |personality| {
+        eval::evaluate_with(&near, personality) - eval::evaluate_with(&far, personality)
+    };
+
+    assert_eq!(
+        eval::evaluate_with(&near, Personality::Balanced),
+        eval::evaluate(&near)
+    );
+    assert!(gain(Personality::Aggressive) > gain(Personality::Balanced));
+}
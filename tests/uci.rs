@@ -0,0 +1,226 @@
+use std::time::Duration;
+
+use bevy_project::uci::{self, Engine, Go};
+use bevy_project::{fen, rules};
+
+/// An executable shell script running `body`, in a temporary directory of its own for the test `name`
+#[cfg(unix)]
+fn engine_script(name: &str, body: &str) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir().join(format!("uci-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let script = dir.join("engine.sh");
+    std::fs::write(&script, format!("#!/bin/sh\n{}", body)).unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    script
+}
+
+/// A stand-in engine answering every `go` with `e7e5`, logging what it was sent to `log`
+#[cfg(unix)]
+fn stand_in_engine(name: &str) -> (std::path::PathBuf, std::path::PathBuf) {
+    let script = engine_script(
+        name,
+        r#"while read -r line; do
+    echo "$line" >> "$(dirname "$0")/log.txt"
+    case "$line" in
+        uci) echo "id name Stand-in"; echo "uciok" ;;
+        isready) echo "readyok" ;;
+        go*) echo "info depth 1 score cp 0"; echo "bestmove e7e5 ponder g1f3" ;;
+        quit) exit 0 ;;
+    esac
+done
+"#,
+    );
+    let log = script.with_file_name("log.txt");
+
+    (script, log)
+}
+
+#[test]
+fn long_algebraic_round_trip() {
+    let position = fen::parse("4k3/1P6/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+
+    for name in ["b7b8q", "b7b8n", "e1g1", "h1h8"] {
+        let m = uci::parse_move(&position, name).unwrap();
+        assert_eq!(uci::move_name(m), name);
+    }
+
+    for invalid in ["b7b8", "b7b8k", "e1e3", "e1", "z9z9"] {
+        assert!(uci::parse_move(&position, invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn commands() {
+    let start = rules::Position::start();
+    let e4 = uci::parse_move(&start, "e2e4").unwrap();
+
+    assert_eq!(uci::position_command(&start, &[]), "position startpos");
+    assert_eq!(
+        uci::position_command(&start, &[e4]),
+        "position startpos moves e2e4"
+    );
+
+    let position = fen::parse("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        uci::position_command(&position, &[]),
+        "position fen 4k3/8/8/8/8/8/8/4K3 w - - 0 1"
+    );
+
+    let go = Go::Clocks {
+        white: Duration::from_secs(60),
+        black: Duration::from_millis(59_500),
+        white_increment: Duration::from_secs(2),
+        black_increment: Duration::from_secs(2),
+    };
+    assert_eq!(
+        go.command(),
+        "go wtime 60000 btime 59500 winc 2000 binc 2000"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn plays_against_a_child_process() {
+    let (script, log) = stand_in_engine("play");
+
+    let start = rules::Position::start();
+    let e4 = uci::parse_move(&start, "e2e4").unwrap();
+    let reply = {
+        let mut engine = Engine::start(&script).unwrap();
+        assert_eq!(engine.name.as_deref(), Some("Stand-in"));
+
+        engine.new_game().unwrap();
+        engine
+            .best_move(&start, &[e4], Go::MoveTime(Duration::from_millis(100)))
+            .unwrap()
+    };
+
+    assert_eq!(uci::move_name(reply), "e7e5");
+    let sent = std::fs::read_to_string(&log).unwrap();
+    assert_eq!(
+        sent.lines().collect::<Vec<_>>(),
+        [
+            "uci",
+            "isready",
+            "ucinewgame",
+            "isready",
+            "position startpos moves e2e4",
+            "go movetime 100",
+            "quit"
+        ]
+    );
+
+    // NOTE: The stand-in always answers e7e5, which White cannot play
+    let mut engine = Engine::start(&script).unwrap();
+    assert!(engine
+        .best_move(&start, &[], Go::MoveTime(Duration::from_millis(100)))
+        .is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn gives_up_on_a_silent_engine() {
+    let script = engine_script("silent", "cat > /dev/null\n");
+
+    let error = Engine::start(&script).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+fn reads_position_and_go_commands() {
+    let position = uci::parse_position("startpos moves e2e4 e7e5 g1f3").unwrap();
+    assert_eq!(
+        fen::write(&position),
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+    );
+
+    let position = uci::parse_position("fen 4k3/8/8/8/8/8/8/4K3 w - - 0 1 moves e1d2").unwrap();
+    assert_eq!(fen::write(&position), "4k3/8/8/8/8/8/3K4/8 b - - 1 1");
+
+    assert!(uci::parse_position("startpos moves e2e5").is_err());
+    assert!(uci::parse_position("somewhere").is_err());
+
+    let limits = uci::parse_go("depth 3 nodes 1000", rules::PieceColor::White);
+    assert_eq!(
+        (limits.depth, limits.nodes, limits.time),
+        (3, Some(1000), None)
+    );
+
+    let limits = uci::parse_go("movetime 250", rules::PieceColor::White);
+    assert_eq!(limits.time, Some(Duration::from_millis(250)));
+
+    let go = "wtime 60000 btime 30000 winc 1000 binc 2000 movestogo 10";
+    let limits = uci::parse_go(go, rules::PieceColor::Black);
+    assert_eq!(limits.time, Some(Duration::from_millis(4000)));
+}
+
+#[test]
+fn headless_binary_speaks_uci() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_uci"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+
+    writeln!(
+        stdin,
+        "uci\nisready\nposition startpos moves e2e4\ngo depth 3"
+    )
+    .unwrap();
+    let mut best = None;
+    for line in stdout.lines() {
+        let line = line.unwrap();
+        if let Some(name) = line.strip_prefix("bestmove ") {
+            best = Some(name.to_string());
+            break;
+        }
+    }
+    writeln!(stdin, "quit").unwrap();
+    child.wait().unwrap();
+
+    let position = uci::parse_position("startpos moves e2e4").unwrap();
+    assert!(uci::parse_move(&position, &best.unwrap()).is_ok());
+}
+
+#[test]
+fn headless_binary_holds_infinite_searches_until_stop() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_uci"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+
+    // NOTE: The depth 1 search is over long before `isready`, only its `bestmove` is held back
+    writeln!(stdin, "position startpos\ngo infinite depth 1").unwrap();
+    std::thread::sleep(Duration::from_millis(200));
+    writeln!(stdin, "isready").unwrap();
+    for line in lines.by_ref() {
+        let line = line.unwrap();
+        assert!(!line.starts_with("bestmove"), "{}", line);
+        if line == "readyok" {
+            break;
+        }
+    }
+
+    writeln!(stdin, "stop").unwrap();
+    let best = lines
+        .map(Result::unwrap)
+        .find(|line| line.starts_with("bestmove"));
+    writeln!(stdin, "quit").unwrap();
+    child.wait().unwrap();
+
+    assert!(best.is_some());
+}